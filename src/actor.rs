@@ -1,7 +1,8 @@
 use std::any::Any;
-use std::collections::HashSet;
 
 pub use crate::pt2d::*;
+use crate::input::InputState;
+use crate::rand::Rng;
 
 pub trait Actor 
 {
@@ -11,6 +12,7 @@ pub trait Actor
     fn sprite(&self) -> Option<Pt>;
     fn alive(&self) -> bool;
     fn as_any(&self) -> &dyn Any;
+    fn clone_dyn(&self) -> Box<dyn Actor>;
 }
 
 pub struct ArenaStatus<'a> 
@@ -20,36 +22,36 @@ pub struct ArenaStatus<'a>
     others: Vec<&'a mut Box<dyn Actor>>,
     size: Pt,
     count: i32,
-    curr_keys: String,
-    prev_keys: String
+    rng: &'a mut Rng,
+    input: &'a InputState
 }
 impl ArenaStatus<'_> 
 {
     pub fn spawn(&mut self, b: Box<dyn Actor>) { self.spawned.push(b); }
     pub fn size(&self) -> Pt { self.size }
     pub fn count(&self) -> i32 { self.count }
-    pub fn current_keys(&self) -> Vec<&str> { self.curr_keys.split(",").collect() }
-    pub fn previous_keys(&self) -> Vec<&str> { self.prev_keys.split(",").collect() }
+    pub fn randint(&mut self, nmin: i32, nmax: i32) -> i32 { self.rng.randint(nmin, nmax) }
+    pub fn input(&self) -> &InputState { self.input }
     pub fn collisions(&self) -> &Vec<&mut Box<dyn Actor>> { &self.collisions }
     pub fn others(&self) -> &Vec<&mut Box<dyn Actor>> { &self.others }
 }
 
-
+#[derive(Clone)]
 pub struct Arena 
 {
     size: Pt,
     count: i32,
-    prev_keys: String,
+    rng: Rng,
     actors: Vec<Box<dyn Actor>>
 }
 impl Arena 
 {
-    pub fn new(size: Pt) -> Arena 
+    pub fn new(size: Pt, rng: Rng) -> Arena 
     {
         Arena {
             size: size,
             count: 0,
-            prev_keys: String::new(),
+            rng,
             actors: vec![],
         }
     }
@@ -63,14 +65,22 @@ impl Arena
             && tl2.x < br1.x && tl1.x < br2.x
             && tl2.y < br1.y && tl1.y < br2.y
     }
-    pub fn tick(&mut self, keys: String) 
+    /// Size of the tiles the arena is divided in, for efficient collision detection.
+    pub fn tile_size(&self) -> Pt { pt(40, 40) }
+
+    /// Number of tiles along each axis.
+    pub fn tiles(&self) -> Pt
     {
-        // divide the arena in tiles, for efficient collision detection
-        let tile = pt(40, 40);
-        let n = (self.size() + tile - pt(1, 1)) / tile;  // ceil
-        let mut cells: Vec<HashSet<usize>> = vec![];
-        
-        for _ in 0..n.x * n.y { cells.push(HashSet::new()); }
+        let tile = self.tile_size();
+        (self.size() + tile - pt(1, 1)) / tile  // ceil
+    }
+
+    /// For each tile (row by row), the indexes of the actors overlapping it.
+    fn cells(&self) -> Vec<Vec<usize>>
+    {
+        let tile = self.tile_size();
+        let n = self.tiles();
+        let mut cells: Vec<Vec<usize>> = vec![vec![]; (n.x * n.y) as usize];
         
         for (i, b) in self.actors.iter().enumerate() 
         {
@@ -82,18 +92,27 @@ impl Arena
                 {
                     if 0 <= x && x < n.x &&  0 <= y && y < n.y 
                     {
-                        cells[(y * n.x + x) as usize].insert(i);
+                        cells[(y * n.x + x) as usize].push(i);
                     }
                 }
             }
         }
 
-        let mut collisions: Vec<HashSet<usize>> = vec![];
+        cells
+    }
+
+    /// For each actor, the sorted indexes of the actors colliding with it.
+    fn collisions(&self) -> Vec<Vec<usize>>
+    {
+        let tile = self.tile_size();
+        let n = self.tiles();
+        let cells = self.cells();
+        let mut collisions: Vec<Vec<usize>> = vec![];
 
         for (i, b) in self.actors.iter().enumerate() 
         {
             let (tl, br) = (b.pos() / tile, (b.pos() + b.size()) / tile);
-            let mut neighs = HashSet::<usize>::new();
+            let mut neighs: Vec<usize> = vec![];
 
             for x in tl.x..=br.x 
             {
@@ -106,11 +125,35 @@ impl Arena
                 }
             }
 
-            neighs.remove(&i);
+            neighs.sort_unstable();
+            neighs.dedup();
+            neighs.retain(|j| *j != i);
             neighs.retain(|j| Arena::check_collision(&**b, &*self.actors[*j]));
             collisions.push(neighs);
         }
 
+        collisions
+    }
+
+    /// Number of actors overlapping each tile, row by row.
+    pub fn occupancy(&self) -> Vec<usize> { self.cells().iter().map(|c| c.len()).collect() }
+
+    /// Pairs of indexes of the actors currently colliding, each pair listed once.
+    pub fn collision_pairs(&self) -> Vec<(usize, usize)>
+    {
+        let mut pairs: Vec<(usize, usize)> = vec![];
+
+        for (i, neighs) in self.collisions().iter().enumerate()
+        {
+            pairs.extend(neighs.iter().filter(|j| **j > i).map(|j| (i, *j)));
+        }
+
+        pairs
+    }
+
+    pub fn tick(&mut self, input: &InputState) 
+    {
+        let collisions = self.collisions();
         let mut spawned: Vec<Box<dyn Actor>> = vec![];
         
         for i in 0..self.actors.len() 
@@ -122,8 +165,8 @@ impl Arena
                 others: vec![],
                 size: self.size,
                 count: self.count,
-                curr_keys: keys.to_string(),
-                prev_keys: self.prev_keys.to_string()
+                rng: &mut self.rng,
+                input
             };
 
             let (left, right) = self.actors.split_at_mut(i);
@@ -133,7 +176,7 @@ impl Arena
 
             for (j, o) in others.enumerate() 
             {
-                if (&collisions[i]).contains(&(j + (j >= i) as usize)) 
+                if (&collisions[i]).binary_search(&(j + (j >= i) as usize)).is_ok() 
                 {
                     status.collisions.push(o);
                 } 
@@ -150,16 +193,14 @@ impl Arena
         self.count += 1;
         self.actors.append(&mut spawned);
         self.actors.retain(|b| b.alive());
-        self.prev_keys = keys.to_string();
     }
     
     pub fn spawn(&mut self, b: Box<dyn Actor>) { self.actors.push(b); }
     pub fn actors(&self) -> &Vec<Box<dyn Actor>> { &self.actors }
     pub fn size(&self) -> Pt { self.size }
     pub fn count(&self) -> i32 { self.count }
+    pub fn rng(&self) -> &Rng { &self.rng }
 }
 
-//pub trait Actor { fn clone_dyn(&self) -> Box<dyn Actor>; }
-//impl Actor for Ball { fn clone_dyn... { Box::new(self.clone()) } }
-//impl Clone for Box<dyn Actor> { fn clone(&self) -> Self { self.clone_dyn() } }
+impl Clone for Box<dyn Actor> { fn clone(&self) -> Self { self.clone_dyn() } }
 
@@ -0,0 +1,153 @@
+use wasm_bindgen::prelude::*;
+
+use crate::frogger::*;
+use crate::input::*;
+use crate::pt2d::*;
+
+/// Options for a new game, settable from JavaScript.
+#[wasm_bindgen]
+#[derive(Copy, Clone, Debug)]
+pub struct GameOptions 
+{
+    pub width: i32,
+    pub height: i32,
+    pub seed: u32,
+    pub level: i32,
+    pub vehicles_per_row: i32,
+    pub rafts_per_row: i32,
+    pub players: usize,
+    mode: Mode
+}
+#[wasm_bindgen]
+impl GameOptions 
+{
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> GameOptions 
+    {
+        let config = GameConfig::default();
+
+        GameOptions { width: config.size.x, 
+                      height: config.size.y, 
+                      seed: config.seed, 
+                      level: config.level, 
+                      vehicles_per_row: config.n_vehicle_per_row, 
+                      rafts_per_row: config.n_raft_per_row,
+                      players: config.players,
+                      mode: config.mode }
+    }
+
+    /// How two players share the board: Turns, Coop or Versus.
+    pub fn mode(&self) -> String { format!("{:?}", self.mode) }
+
+    pub fn set_mode(&mut self, name: String) -> Result<(), JsValue> 
+    {
+        self.mode = Mode::from_name(&name).ok_or_else(|| JsValue::from_str(&format!("Unknown mode: {}", name)))?;
+        Ok(())
+    }
+}
+impl Default for GameOptions 
+{
+    fn default() -> Self { Self::new() }
+}
+
+/// Number of values describing each actor in `GameHandle::actors`.
+pub const ACTOR_FIELDS: usize = 7;
+
+/// A game that JavaScript can create, drive and inspect, independently of the canvas GUI.
+/// Any number of games can live in the same page.
+#[wasm_bindgen(js_name = FroggerGame)]
+pub struct GameHandle 
+{
+    game: FroggerGame,
+    input: InputState
+}
+#[wasm_bindgen(js_class = FroggerGame)]
+impl GameHandle 
+{
+    /// Fails if the options are out of the limits of `GameConfig::check`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(options: &GameOptions) -> Result<GameHandle, JsValue> 
+    {
+        let config = GameConfig { size: pt(options.width, options.height),
+                                  seed: options.seed,
+                                  level: options.level,
+                                  n_vehicle_per_row: options.vehicles_per_row,
+                                  n_raft_per_row: options.rafts_per_row,
+                                  players: options.players,
+                                  mode: options.mode };
+
+        config.check().map_err(|e| JsValue::from_str(&e))?;
+        Ok(GameHandle { game: FroggerGame::with_config(config), input: InputState::new() })
+    }
+
+    /// Advance the game by one tick; `keys` is the comma separated list of the keys held down.
+    pub fn tick(&mut self, keys: String) 
+    { 
+        self.input.update(parse_keys(&keys));
+        self.game.tick(&self.input);
+    }
+
+    /// Like `tick`, also giving the keys pressed since the previous tick in order, even if already released.
+    pub fn tick_with_presses(&mut self, keys: String, presses: String) 
+    { 
+        self.input.update_with_presses(parse_keys(&keys), parse_key_list(&presses));
+        self.game.tick(&self.input);
+    }
+
+    /// Change the controls, given a preset name (arrows, wasd, vim) or a bindings configuration.
+    pub fn set_key_bindings(&mut self, config: String) -> Result<(), JsValue> 
+    {
+        self.set_player_key_bindings(0, config)
+    }
+
+    /// Change the controls of a player, when two players play at the same time (the second one uses WASD by default).
+    pub fn set_player_key_bindings(&mut self, player: usize, config: String) -> Result<(), JsValue> 
+    {
+        let bindings = KeyBindings::from_config(&config).map_err(|e| JsValue::from_str(&e))?;
+        self.input.set_player_bindings(player, bindings).map_err(|e| JsValue::from_str(&e))
+    }
+
+    pub fn width(&self) -> i32 { self.game.size().x }
+    pub fn height(&self) -> i32 { self.game.size().y }
+    pub fn score(&self) -> i32 { self.game.score() }
+    pub fn lives(&self) -> i32 { self.game.remaining_lives() }
+    pub fn level(&self) -> i32 { self.game.level() }
+    pub fn player_score(&self, player: usize) -> i32 { self.game.player_score(player) }
+    pub fn player_lives(&self, player: usize) -> i32 { self.game.player_lives(player) }
+
+    /// Index of the player with the highest score, or -1 if tied or playing alone.
+    pub fn winner(&self) -> i32 { self.game.winner().map_or(-1, |p| p as i32) }
+
+    /// Index of the player whose turn it is, in two players games.
+    pub fn turn(&self) -> usize { self.game.turn() }
+    pub fn playing_time(&self) -> i32 { self.game.playing_time() }
+    pub fn remaining_time(&self) -> i32 { self.game.remaining_time() }
+
+    /// Name of the current phase: Ready, Playing, Dying, LevelClear, GameOver or Victory.
+    pub fn phase(&self) -> String { format!("{:?}", self.game.phase()) }
+
+    /// Flat list of the actors, `ACTOR_FIELDS` values each: kind, x, y, width, height, sprite x, sprite y.
+    /// The kind is the index in: Frog, Vehicle, Raft, Turtle, Crocodile, Water, Other.
+    /// Sprite coordinates are -1 for actors not to be drawn.
+    pub fn actors(&self) -> Vec<i32> 
+    {
+        let mut values: Vec<i32> = Vec::with_capacity(self.game.actors().len() * ACTOR_FIELDS);
+
+        for b in self.game.actors() 
+        {
+            let sprite = b.sprite().unwrap_or(pt(-1, -1));
+            values.extend([ActorKind::of(&**b) as i32, b.pos().x, b.pos().y, b.size().x, b.size().y, sprite.x, sprite.y]);
+        }
+
+        values
+    }
+
+    /// Home slots from left to right: 0 if free, otherwise the number of the player who took it (1 or 2).
+    pub fn homes(&self) -> Vec<u8> 
+    {
+        self.game.homes().iter().map(|h| h.map_or(0, |p| p as u8 + 1)).collect()
+    }
+
+    /// Release the game; the same as calling `free()` from JavaScript.
+    pub fn destroy(self) { }
+}
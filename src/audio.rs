@@ -0,0 +1,218 @@
+use crate::frogger::{DeathCause, Phase};
+use crate::g2d;
+
+/// Sounds of the events of a game, as reported by `FroggerGame::sounds` after each tick.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Sound
+{
+    Hop,
+    Squash,        // Hit by a vehicle, eaten by a crocodile, or out of time
+    Splash,        // Fallen in the water, or missed the home slot
+    Home,
+    LevelClear,
+    TimeWarning,   // Little time left to reach a home slot
+    ExtraLife
+}
+impl Sound
+{
+    pub const ALL: [Sound; 7] = [Sound::Hop, Sound::Squash, Sound::Splash, Sound::Home, Sound::LevelClear, Sound::TimeWarning, Sound::ExtraLife];
+
+    pub fn of_death(cause: DeathCause) -> Sound
+    {
+        match cause
+        {
+            DeathCause::Drowned | DeathCause::MissedHome => Sound::Splash,
+            DeathCause::Squashed | DeathCause::Eaten | DeathCause::TimedOut => Sound::Squash
+        }
+    }
+
+    /// Name of the sound, also used for its file: `sounds/hop.wav` and so on.
+    pub fn name(&self) -> &'static str
+    {
+        match self
+        {
+            Sound::Hop => "hop",
+            Sound::Squash => "squash",
+            Sound::Splash => "splash",
+            Sound::Home => "home",
+            Sound::LevelClear => "level-clear",
+            Sound::TimeWarning => "time-warning",
+            Sound::ExtraLife => "extra-life"
+        }
+    }
+
+    pub fn file(&self) -> String { format!("sounds/{}.wav", self.name()) }
+}
+
+/// Music looping during each phase of the game, and on the title screens.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Track
+{
+    Title,
+    Playing,
+    LevelClear,
+    GameOver
+}
+impl Track
+{
+    /// Track of a phase of the game: the level music keeps playing through deaths.
+    pub fn of(phase: Phase) -> Track
+    {
+        match phase
+        {
+            Phase::Ready | Phase::Playing | Phase::Dying => Track::Playing,
+            Phase::LevelClear | Phase::Victory => Track::LevelClear,
+            Phase::GameOver => Track::GameOver
+        }
+    }
+
+    /// Name of the track, also used for its file: `music/title.wav` and so on.
+    pub fn name(&self) -> &'static str
+    {
+        match self
+        {
+            Track::Title => "music-title",
+            Track::Playing => "music-playing",
+            Track::LevelClear => "music-level-clear",
+            Track::GameOver => "music-game-over"
+        }
+    }
+
+    pub fn file(&self) -> String { format!("music/{}.wav", &self.name()["music-".len()..]) }
+}
+
+/// Volumes of the sounds, from 0.0 to 1.0: music and effects are also scaled by the master volume.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct AudioSettings
+{
+    pub master: f64,
+    pub music: f64,
+    pub effects: f64,
+    pub muted: bool
+}
+impl Default for AudioSettings
+{
+    fn default() -> Self
+    {
+        AudioSettings { master: 0.8, music: 0.5, effects: 1.0, muted: false }
+    }
+}
+impl AudioSettings
+{
+    pub fn music_volume(&self) -> f64 { if self.muted { 0.0 } else { self.master * self.music } }
+    pub fn effects_volume(&self) -> f64 { if self.muted { 0.0 } else { self.master * self.effects } }
+
+    /// Change a volume given its name (master, music or effects), keeping it between 0.0 and 1.0,
+    /// in hundredths.
+    pub fn set_volume(&mut self, name: &str, volume: f64) -> Result<(), String>
+    {
+        let volume = if volume.is_nan() { 0.0 } else { (volume.clamp(0.0, 1.0) * 100.0).round() / 100.0 };
+
+        match name.trim().to_lowercase().as_str()
+        {
+            "master" => self.master = volume,
+            "music" => self.music = volume,
+            "effects" => self.effects = volume,
+            _ => return Err(format!("Unknown volume: {}", name))
+        }
+        Ok(())
+    }
+}
+
+/// Music played by the browser, cross-fading from a track to the next one.
+pub struct Music
+{
+    track: Option<Track>,
+    volume: f64,
+    fade: f64   // Seconds of a cross-fade
+}
+impl Default for Music
+{
+    fn default() -> Self { Self::new() }
+}
+impl Music
+{
+    pub fn new() -> Music { Music { track: None, volume: 1.0, fade: 1.0 } }
+
+    pub fn track(&self) -> Option<Track> { self.track }
+
+    /// Play the given track at the given volume, or fade out to silence with `None`. Calls with the
+    /// track already playing only change its volume, if different.
+    pub fn update(&mut self, track: Option<Track>, volume: f64)
+    {
+        if track != self.track
+        {
+            let (name, file) = track.map_or((String::new(), String::new()), |t| (t.name().to_string(), t.file()));
+            g2d::play_music(name, file, volume, self.fade);
+        }
+        else if volume != self.volume { g2d::set_music_volume(volume); }
+
+        self.track = track;
+        self.volume = volume;
+    }
+}
+
+/// Where the sounds of a game are played.
+pub trait SoundSink
+{
+    fn play(&mut self, sound: Sound);
+
+    fn play_all(&mut self, sounds: &[Sound])
+    {
+        for sound in sounds { self.play(*sound); }
+    }
+}
+
+/// Sounds played by the browser through Web Audio.
+pub struct WebAudio
+{
+    loaded: bool,
+    volume: f64
+}
+impl Default for WebAudio
+{
+    fn default() -> Self { Self::new() }
+}
+impl WebAudio
+{
+    pub fn new() -> WebAudio { WebAudio { loaded: false, volume: 1.0 } }
+
+    pub fn set_volume(&mut self, volume: f64) { self.volume = volume; }
+
+    /// Start loading the files of all the sounds, so that they are ready when played first.
+    pub fn load(&mut self)
+    {
+        for sound in Sound::ALL.iter() { g2d::load_sound(sound.name().to_string(), sound.file()); }
+        self.loaded = true;
+    }
+}
+impl SoundSink for WebAudio
+{
+    fn play(&mut self, sound: Sound)
+    {
+        if !self.loaded { self.load(); }
+        if self.volume > 0.0 { g2d::play_sound(sound.name().to_string(), self.volume); }
+    }
+}
+
+/// Sounds played in native programs, where there is no audio: they are only kept, and printed if asked.
+#[derive(Clone, Debug, Default)]
+pub struct LogSink
+{
+    pub played: Vec<Sound>,
+    pub print: bool
+}
+impl LogSink
+{
+    pub fn new(print: bool) -> LogSink { LogSink { played: vec![], print } }
+
+    pub fn count(&self, sound: Sound) -> usize { self.played.iter().filter(|s| **s == sound).count() }
+}
+impl SoundSink for LogSink
+{
+    fn play(&mut self, sound: Sound)
+    {
+        if self.print { println!("sound {}", sound.name()); }
+        self.played.push(sound);
+    }
+}
@@ -0,0 +1,402 @@
+//! Serve the game and a leaderboard of the results submitted by the players.
+//!
+//! `leaderboard serve [ADDR] [DATA_FILE] [ROOT_DIR]` serves the files in `ROOT_DIR` (the wasm bundle,
+//! the page and the images) at `ADDR`, 127.0.0.1:8000 by default, and the leaderboard API:
+//! - `GET /api/scores?n=10`: the best results, as JSON;
+//! - `GET /api/levels/LEVEL/scores?n=10`: the best results of the games started at the given level;
+//! - `POST /api/scores`: submit a result, as `name`, `player` and `score` lines followed by the replay
+//!   of the game. Results are accepted only if playing the replay again gives the same score, and
+//!   once for each game. A few replays are played at the same time; requests beyond are turned down.
+//!
+//! Accepted results are appended to `DATA_FILE`, leaderboard.txt by default. Malformed lines of the file
+//! are skipped.
+//!
+//! `leaderboard verify [REPLAY_FILE]` plays again the game recorded in a replay file (the standard input
+//! if missing or `-`) and prints its outcome: scores, lives, levels, home slots and ticks.
+
+use std::collections::HashMap;
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use hello_wasm::replay::{self, Replay};
+use hello_wasm::scores::{self, HighScore};
+
+/// Largest request body accepted, in bytes.
+const MAX_BODY: usize = 1 << 20;
+/// Longest replay accepted: one hour of play.
+const MAX_REPLAY_TICKS: i32 = 60 * 60 * 30;
+/// Longest wait for a client to send its request, or to read the response.
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(10);
+/// Results listed when not given.
+const DEFAULT_TOP: usize = 10;
+/// Most replays played again at the same time, each keeping a core busy.
+const MAX_VERIFICATIONS: usize = 4;
+
+/// Replays being played again.
+static VERIFICATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// A place among the `MAX_VERIFICATIONS` replays played at the same time, left when dropped.
+struct Slot;
+impl Slot
+{
+    fn take() -> Option<Slot>
+    {
+        VERIFICATIONS.fetch_update(Ordering::SeqCst, Ordering::SeqCst, |n| if n < MAX_VERIFICATIONS { Some(n + 1) } else { None })
+                     .ok()
+                     .map(|_| Slot)
+    }
+}
+impl Drop for Slot
+{
+    fn drop(&mut self) { VERIFICATIONS.fetch_sub(1, Ordering::SeqCst); }
+}
+
+/// A result, the configuration of the game it was got in, and the digest of its replay.
+#[derive(Clone, Debug)]
+struct Entry
+{
+    result: HighScore,
+    start_level: i32,
+    seed: u32,
+    replay: u64
+}
+impl Entry
+{
+    fn to_line(&self) -> String
+    {
+        format!("{}\t{}\t{}\t{:016x}", self.result.to_line(), self.start_level, self.seed, self.replay)
+    }
+
+    fn from_line(line: &str) -> Result<Entry, String>
+    {
+        let mut fields = line.rsplitn(4, '\t');
+        let replay = u64::from_str_radix(fields.next().unwrap_or("").trim(), 16).map_err(|e| format!("Bad replay digest in {}: {}", line, e))?;
+        let seed = fields.next().unwrap_or("").trim().parse::<u32>().map_err(|e| format!("Bad seed in {}: {}", line, e))?;
+        let start_level = fields.next().unwrap_or("").trim().parse::<i32>().map_err(|e| format!("Bad level in {}: {}", line, e))?;
+        let result = HighScore::from_line(fields.next().unwrap_or(""))?;
+
+        Ok(Entry { result, start_level, seed, replay })
+    }
+
+    fn to_json(&self, rank: usize) -> String
+    {
+        let r = &self.result;
+        format!("{{\"rank\":{},\"name\":\"{}\",\"score\":{},\"level\":{},\"time\":{},\"date\":\"{}\",\"start_level\":{},\"seed\":{}}}",
+                rank, json_escape(&r.name), r.score, r.level, r.time, json_escape(&r.date), self.start_level, self.seed)
+    }
+}
+
+fn json_escape(text: &str) -> String
+{
+    text.chars().flat_map(|c| match c
+    {
+        '"' => vec!['\\', '"'],
+        '\\' => vec!['\\', '\\'],
+        c if (c as u32) < 0x20 => format!("\\u{:04x}", c as u32).chars().collect(),
+        c => vec![c]
+    }).collect()
+}
+
+/// All the accepted results, also kept in a file, one per line.
+struct Board
+{
+    entries: Vec<Entry>,
+    path: PathBuf
+}
+impl Board
+{
+    fn load(path: &Path) -> Result<Board, String>
+    {
+        let text = match fs::read_to_string(path)
+        {
+            Ok(text) => text,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(format!("{}: {}", path.display(), e))
+        };
+        let mut entries: Vec<Entry> = vec![];
+
+        // A line cut short by a crash should not keep the server from starting
+        for (i, line) in text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty())
+        {
+            match Entry::from_line(line)
+            {
+                Ok(entry) => entries.push(entry),
+                Err(e) => eprintln!("{}:{}: skipped: {}", path.display(), i + 1, e)
+            }
+        }
+
+        // Results are appended on lines of their own, also after a line cut short
+        if !text.is_empty() && !text.ends_with('\n')
+        {
+            let mut file = OpenOptions::new().append(true).open(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            writeln!(file).map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+
+        Ok(Board { entries, path: path.to_path_buf() })
+    }
+
+    /// Whether a result was already accepted for the game of the replay with the given digest.
+    fn contains(&self, replay: u64) -> bool { self.entries.iter().any(|e| e.replay == replay) }
+
+    fn add(&mut self, entry: Entry) -> Result<usize, String>
+    {
+        let mut file = OpenOptions::new().create(true).append(true).open(&self.path).map_err(|e| format!("{}: {}", self.path.display(), e))?;
+        writeln!(file, "{}", entry.to_line()).map_err(|e| format!("{}: {}", self.path.display(), e))?;
+
+        let result = entry.result.clone();
+        self.entries.push(entry);
+        Ok(self.top(None, usize::MAX).iter().position(|e| e.result == result).unwrap_or(0) + 1)
+    }
+
+    /// Best `n` results, of the games started at the given level if any: by score, then by time.
+    fn top(&self, start_level: Option<i32>, n: usize) -> Vec<&Entry>
+    {
+        let mut entries: Vec<&Entry> = self.entries.iter().filter(|e| start_level.is_none() || start_level == Some(e.start_level)).collect();
+
+        entries.sort_by_key(|e| (-e.result.score, e.result.time));
+        entries.truncate(n);
+        entries
+    }
+}
+
+/// Check a submitted result against its replay, returning the entry to add to the board.
+fn verify_submission(board: &Mutex<Board>, body: &str) -> Result<Entry, String>
+{
+    let mut fields: HashMap<&str, &str> = HashMap::new();
+    let mut replay_lines: Vec<&str> = vec![];
+
+    for line in body.lines()
+    {
+        match line.split_once(' ')
+        {
+            Some((key, value)) if ["name", "player", "score"].contains(&key) => { fields.insert(key, value.trim()); }
+            _ => replay_lines.push(line)
+        }
+    }
+
+    let name: String = fields.get("name").ok_or("Missing name")?.chars().take(scores::MAX_NAME_LEN).collect();
+    let player = fields.get("player").unwrap_or(&"0").parse::<usize>().map_err(|e| format!("Bad player: {}", e))?;
+    let claimed = fields.get("score").ok_or("Missing score")?.parse::<i32>().map_err(|e| format!("Bad score: {}", e))?;
+    let replay = Replay::from_text(&replay_lines.join("\n"))?;
+
+    if name.trim().is_empty() { return Err("Empty name".to_string()); }
+    if replay.ticks > MAX_REPLAY_TICKS { return Err("Replay too long".to_string()); }
+    replay.check_config()?;
+
+    let digest = replay.digest();
+    if board.lock().unwrap().contains(digest) { return Err("This game was already submitted".to_string()); }
+
+    let outcome = replay::verify(&replay);
+
+    if player >= outcome.scores.len() { return Err(format!("No player {} in the game", player)); }
+    if !outcome.finished { return Err("The replay does not reach the end of the game".to_string()); }
+    if outcome.scores[player] != claimed
+    {
+        return Err(format!("Claimed score {}, but the replay scores {}", claimed, outcome.scores[player]));
+    }
+
+    let result = HighScore { name, score: claimed, level: outcome.levels[player], time: outcome.time, date: scores::today() };
+    Ok(Entry { result, start_level: replay.config.level.max(1), seed: replay.config.seed, replay: digest })
+}
+
+struct Response
+{
+    status: &'static str,
+    content_type: &'static str,
+    body: Vec<u8>
+}
+impl Response
+{
+    fn json(status: &'static str, body: String) -> Response
+    {
+        Response { status, content_type: "application/json", body: body.into_bytes() }
+    }
+
+    fn error(status: &'static str, message: &str) -> Response
+    {
+        Response::json(status, format!("{{\"error\":\"{}\"}}", json_escape(message)))
+    }
+}
+
+fn content_type(path: &Path) -> &'static str
+{
+    match path.extension().and_then(|e| e.to_str()).unwrap_or("")
+    {
+        "html" => "text/html; charset=utf-8",
+        "js" => "text/javascript",
+        "wasm" => "application/wasm",
+        "png" => "image/png",
+        "css" => "text/css",
+        "json" => "application/json",
+        _ => "application/octet-stream"
+    }
+}
+
+/// A file below the root directory; paths leading out of it are not found.
+fn static_file(root: &Path, path: &str) -> Response
+{
+    let relative = if path == "/" { "index.html" } else { path.trim_start_matches('/') };
+
+    if relative.split('/').any(|part| part == ".." || part.starts_with('.'))
+    {
+        return Response::error("404 Not Found", "Not found");
+    }
+
+    let file = root.join(relative);
+
+    match fs::read(&file)
+    {
+        Ok(body) => Response { status: "200 OK", content_type: content_type(&file), body },
+        Err(_) => Response::error("404 Not Found", "Not found")
+    }
+}
+
+fn scores(board: &Mutex<Board>, start_level: Option<i32>, query: &HashMap<String, String>) -> Response
+{
+    let n = query.get("n").and_then(|n| n.parse::<usize>().ok()).unwrap_or(DEFAULT_TOP);
+    let board = board.lock().unwrap();
+    let entries: Vec<String> = board.top(start_level, n).iter().enumerate().map(|(i, e)| e.to_json(i + 1)).collect();
+
+    Response::json("200 OK", format!("[{}]", entries.join(",")))
+}
+
+fn route(board: &Mutex<Board>, root: &Path, method: &str, target: &str, body: &str) -> Response
+{
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let query: HashMap<String, String> = query.split('&').filter_map(|p| p.split_once('=')).map(|(k, v)| (k.to_string(), v.to_string())).collect();
+    let parts: Vec<&str> = path.trim_matches('/').split('/').collect();
+
+    match (method, parts.as_slice())
+    {
+        ("GET", ["api", "scores"]) => scores(board, None, &query),
+        ("GET", ["api", "levels", level, "scores"]) => match level.parse::<i32>()
+        {
+            Ok(level) => scores(board, Some(level), &query),
+            Err(_) => Response::error("400 Bad Request", "Bad level")
+        },
+        ("POST", ["api", "scores"]) => match Slot::take().map(|_slot| verify_submission(board, body))
+        {
+            Some(Ok(entry)) =>
+            {
+                let json = entry.to_json(0);
+                let mut board = board.lock().unwrap();
+
+                // The same game may have been verified meanwhile for another request
+                if board.contains(entry.replay) { return Response::error("422 Unprocessable Entity", "This game was already submitted"); }
+
+                match board.add(entry)
+                {
+                    Ok(rank) => Response::json("201 Created", json.replacen("\"rank\":0", &format!("\"rank\":{}", rank), 1)),
+                    Err(e) => Response::error("500 Internal Server Error", &e)
+                }
+            }
+            Some(Err(e)) => Response::error("422 Unprocessable Entity", &e),
+            None => Response::error("503 Service Unavailable", "Too many results being checked, try again later")
+        },
+        (_, ["api", ..]) => Response::error("404 Not Found", "Not found"),
+        ("GET", _) => static_file(root, path),
+        _ => Response::error("405 Method Not Allowed", "Method not allowed")
+    }
+}
+
+fn handle(board: &Mutex<Board>, root: &Path, stream: TcpStream) -> Result<(), String>
+{
+    // Silent clients would keep their thread forever
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT)).map_err(|e| e.to_string())?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT)).map_err(|e| e.to_string())?;
+
+    let mut reader = BufReader::new(stream.try_clone().map_err(|e| e.to_string())?);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).map_err(|e| e.to_string())?;
+
+    let mut parts = request_line.split_whitespace();
+    let (method, target) = (parts.next().unwrap_or(""), parts.next().unwrap_or("/"));
+    let mut length = 0;
+
+    loop
+    {
+        let mut header = String::new();
+        if reader.read_line(&mut header).map_err(|e| e.to_string())? == 0 || header.trim().is_empty() { break; }
+
+        if let Some((name, value)) = header.split_once(':')
+        {
+            if name.trim().eq_ignore_ascii_case("content-length") { length = value.trim().parse::<usize>().unwrap_or(0); }
+        }
+    }
+
+    let response = if length > MAX_BODY { Response::error("413 Payload Too Large", "Request too large") }
+                   else
+                   {
+                       let mut body = vec![0u8; length];
+                       reader.read_exact(&mut body).map_err(|e| e.to_string())?;
+                       route(board, root, method, target, &String::from_utf8_lossy(&body))
+                   };
+
+    let mut stream = stream;
+    let head = format!("HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+                       response.status, response.content_type, response.body.len());
+    stream.write_all(head.as_bytes()).and_then(|_| stream.write_all(&response.body)).map_err(|e| e.to_string())
+}
+
+fn serve(addr: &str, data: &str, root: &str) -> Result<(), String>
+{
+    let board = Arc::new(Mutex::new(Board::load(Path::new(data))?));
+    let root = PathBuf::from(root);
+    let listener = TcpListener::bind(addr).map_err(|e| format!("{}: {}", addr, e))?;
+
+    println!("Serving {} at http://{}/, results in {}", root.display(), addr, data);
+
+    for stream in listener.incoming().flatten()
+    {
+        let (board, root) = (board.clone(), root.clone());
+        thread::spawn(move || {
+            if let Err(e) = handle(&board, &root, stream) { eprintln!("{}", e); }
+        });
+    }
+
+    Ok(())
+}
+
+/// Print the outcome of the game recorded in a replay file, or read from the standard input if `-`.
+fn verify(path: &str) -> Result<(), String>
+{
+    let text = if path == "-"
+    {
+        let mut text = String::new();
+        std::io::stdin().read_to_string(&mut text).map_err(|e| e.to_string())?;
+        text
+    }
+    else { fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))? };
+
+    let replay = Replay::from_text(&text)?;
+    replay.check_config()?;
+    print!("{}", replay::verify(&replay).to_text());
+    Ok(())
+}
+
+fn main()
+{
+    let args: Vec<String> = env::args().collect();
+    let arg = |i: usize, default: &'static str| args.get(i).map_or(default, |a| a.as_str()).to_string();
+
+    let result = match args.get(1).map(|a| a.as_str())
+    {
+        Some("serve") => serve(&arg(2, "127.0.0.1:8000"), &arg(3, "leaderboard.txt"), &arg(4, ".")),
+        Some("verify") => verify(&arg(2, "-")),
+        _ => Err("Usage: leaderboard serve [ADDR] [DATA_FILE] [ROOT_DIR] | verify [REPLAY_FILE]".to_string())
+    };
+
+    if let Err(e) = result
+    {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,71 @@
+//! Check which seeds of a level let the frog reach every home slot in time. Run
+//! `levelcheck FIRST_SEED LAST_SEED [LEVEL] [VEHICLES_PER_ROW] [RAFTS_PER_ROW]`: seeds that cannot be
+//! solved are listed with the home slots out of reach and the lane blocking the frog.
+
+use std::env;
+use std::process;
+
+use hello_wasm::frogger::*;
+use hello_wasm::solver::*;
+
+const USAGE: &str = "Usage: levelcheck FIRST_SEED LAST_SEED [LEVEL] [VEHICLES_PER_ROW] [RAFTS_PER_ROW]";
+
+fn run(args: &[String]) -> Result<(), String>
+{
+    let number = |i: usize, default: Option<i32>| -> Result<i32, String>
+    {
+        match args.get(i)
+        {
+            Some(arg) => arg.parse::<i32>().map_err(|e| format!("Bad number {}: {}", arg, e)),
+            None => default.ok_or_else(|| USAGE.to_string())
+        }
+    };
+    let seed = |i: usize| -> Result<u32, String>
+    {
+        let arg = args.get(i).ok_or(USAGE)?;
+        arg.parse::<u32>().map_err(|e| format!("Bad seed {}: {}", arg, e))
+    };
+
+    let defaults = GameConfig::default();
+    let config = GameConfig { level: number(3, Some(defaults.level))?,
+                              n_vehicle_per_row: number(4, Some(defaults.n_vehicle_per_row))?,
+                              n_raft_per_row: number(5, Some(defaults.n_raft_per_row))?,
+                              ..defaults };
+    let (first, last) = (seed(1)?, seed(2)?);
+    let mut failing = 0;
+
+    for seed in first..=last
+    {
+        let report = check(&GameConfig { seed, ..config });
+
+        if report.solvable()
+        {
+            let slowest = report.reached.iter().flatten().max().copied().unwrap_or(0);
+            println!("seed {}: ok, last home slot reached after {} ticks", seed, slowest);
+        }
+        else
+        {
+            let slots: Vec<String> = report.unreachable().iter().map(|s| (s + 1).to_string()).collect();
+            let lane = report.blocking.as_ref().map_or("?".to_string(), |lane| {
+                if lane.kinds.is_empty() { format!("row {} (homes)", lane.row) } else { format!("row {} {:?}", lane.row, lane.kinds) }
+            });
+
+            println!("seed {}: home slots {} out of reach, blocked at {}", seed, slots.join(","), lane);
+            failing += 1;
+        }
+    }
+
+    println!("{} of {} seeds cannot be solved at level {}", failing, (last as u64 + 1).saturating_sub(first as u64), config.level);
+    Ok(())
+}
+
+fn main()
+{
+    let args: Vec<String> = env::args().collect();
+
+    if let Err(e) = run(&args)
+    {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,114 @@
+//! Play a co-op game over the network without a GUI, each peer pressing random keys, to check that
+//! the peers stay in sync. Run `netplay host 127.0.0.1:7777` and `netplay join 127.0.0.1:7777` in two
+//! terminals, or `netplay loopback` for both peers in the same process.
+
+use std::env;
+use std::process;
+
+use hello_wasm::frogger::*;
+use hello_wasm::input::*;
+use hello_wasm::net::*;
+use hello_wasm::rand::Rng;
+
+const TICKS: u32 = 3000;
+
+fn config() -> GameConfig
+{
+    GameConfig { seed: 42, players: 2, mode: Mode::Coop, ..GameConfig::default() }
+}
+
+/// A random direction every few ticks, different for each player.
+fn random_actions(rng: &mut Rng, tick: u32) -> Vec<Action>
+{
+    if tick.is_multiple_of(8) { vec![Action::ALL[rng.randint(0, 3) as usize]] } else { vec![] }
+}
+
+fn report<T: Transport>(peer: &Lockstep<T>)
+{
+    let game = peer.game();
+
+    println!("player {} tick {} hash {:016x} phase {:?} scores {} {}", peer.player() + 1, peer.tick(), state_hash(game),
+             game.phase(), game.player_score(0), game.player_score(1));
+}
+
+/// Advance by one tick, waiting for the frames of the other peer.
+fn step<T: Transport>(peer: &mut Lockstep<T>, actions: &[Action]) -> Result<(), String>
+{
+    let mut advanced = peer.update(actions)?;
+
+    while !advanced
+    {
+        std::thread::sleep(std::time::Duration::from_millis(1));
+        advanced = peer.update(&[])?;
+    }
+
+    Ok(())
+}
+
+fn play<T: Transport>(player: usize, transport: T, ticks: u32) -> Result<(), String>
+{
+    let mut peer = Lockstep::new(config(), player, transport);
+    let mut rng = Rng::new(player as u32 + 1);
+
+    while peer.tick() < ticks
+    {
+        let actions = random_actions(&mut rng, peer.tick());
+        step(&mut peer, &actions)?;
+
+        if peer.tick().is_multiple_of(100) { report(&peer); }
+    }
+
+    match peer.desync()
+    {
+        Some(tick) => Err(format!("Desync detected at tick {}", tick)),
+        None => Ok(())
+    }
+}
+
+fn loopback(ticks: u32) -> Result<(), String>
+{
+    let (t1, t2) = LoopbackTransport::pair();
+    let mut peers = [Lockstep::new(config(), 0, t1), Lockstep::new(config(), 1, t2)];
+    let mut rngs = [Rng::new(1), Rng::new(2)];
+
+    while peers[0].tick() < ticks
+    {
+        let tick = peers[0].tick();
+
+        for (peer, rng) in peers.iter_mut().zip(rngs.iter_mut())
+        {
+            peer.update(&random_actions(rng, tick))?;
+        }
+
+        // The first peer received the frame of the second one only after sending its own
+        peers[0].update(&[])?;
+
+        if peers[0].tick().is_multiple_of(100) { report(&peers[0]); }
+    }
+
+    match peers.iter().find_map(|p| p.desync())
+    {
+        Some(tick) => Err(format!("Desync detected at tick {}", tick)),
+        None => Ok(())
+    }
+}
+
+fn main()
+{
+    let args: Vec<String> = env::args().collect();
+    let ticks = |i: usize| args.get(i).and_then(|t| t.parse::<u32>().ok()).unwrap_or(TICKS);
+
+    let result = match (args.get(1).map(|a| a.as_str()), args.get(2)) 
+    {
+        (Some("host"), Some(addr)) => TcpTransport::listen(addr.as_str()).and_then(|t| play(0, t, ticks(3))),
+        (Some("join"), Some(addr)) => TcpTransport::connect(addr.as_str()).and_then(|t| play(1, t, ticks(3))),
+        (Some("loopback"), _) => loopback(ticks(2)),
+        _ => Err("Usage: netplay host ADDR [TICKS] | netplay join ADDR [TICKS] | netplay loopback [TICKS]".to_string())
+    };
+
+    if let Err(e) = result 
+    {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
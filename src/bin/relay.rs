@@ -0,0 +1,256 @@
+//! Relay the stream of a game to local spectators.
+//!
+//! `relay serve PUB_ADDR SUB_ADDR` accepts games publishing their stream (one line per tick) at
+//! `PUB_ADDR`, either over plain TCP or, from browsers, as batches of lines in POST requests; and
+//! spectators at `SUB_ADDR`: plain TCP clients receive the lines as they are, while browsers (HTTP
+//! requests) receive them as server-sent events. New spectators first receive the
+//! lines since the last full frame, so that they can start drawing at once.
+//!
+//! `relay demo PUB_ADDR` publishes a headless game with random moves, and `relay watch SUB_ADDR`
+//! prints a summary of the game received as a spectator.
+
+use std::env;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::process;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use hello_wasm::frogger::*;
+use hello_wasm::input::*;
+use hello_wasm::rand::Rng;
+use hello_wasm::stream::*;
+
+/// Lines waiting to be sent to a spectator, before it is dropped for not keeping up: ten seconds of game.
+const QUEUE_LINES: usize = 30 * 10;
+/// Largest batch of lines published in a POST request.
+const MAX_BODY: usize = 1 << 20;
+
+struct Subscriber
+{
+    stream: TcpStream,
+    http: bool
+}
+impl Subscriber
+{
+    fn send(&mut self, line: &str) -> bool
+    {
+        let data = if self.http { format!("data: {}\n\n", line) } else { format!("{}\n", line) };
+        self.stream.write_all(data.as_bytes()).is_ok()
+    }
+
+    /// Send the queued lines until the spectator leaves, or the hub drops it.
+    fn forward(&mut self, lines: Receiver<String>)
+    {
+        for line in lines
+        {
+            if !self.send(&line) { break; }
+        }
+    }
+}
+
+/// Lines since the last full frame, and the queues of the spectators to forward the next lines to.
+/// Each spectator is written to by its own thread, so that a slow one does not hold up the publisher
+/// nor the other spectators.
+#[derive(Default)]
+struct Hub
+{
+    backlog: Vec<String>,
+    subscribers: Vec<SyncSender<String>>
+}
+impl Hub
+{
+    fn publish(&mut self, line: &str)
+    {
+        if line.starts_with("K ") { self.backlog.clear(); }
+        self.backlog.push(line.to_string());
+        self.subscribers.retain(|queue| queue.try_send(line.to_string()).is_ok());
+    }
+
+    /// Queue of the lines for a new spectator, starting with the ones since the last full frame.
+    fn subscribe(&mut self) -> Option<Receiver<String>>
+    {
+        let (queue, lines) = mpsc::sync_channel(QUEUE_LINES);
+
+        if !self.backlog.iter().all(|line| queue.try_send(line.clone()).is_ok()) { return None; }
+        self.subscribers.push(queue);
+        Some(lines)
+    }
+}
+
+fn publish_lines(hub: &Mutex<Hub>, text: &str)
+{
+    let mut hub = hub.lock().unwrap();
+    for line in text.lines().map(|line| line.trim()).filter(|line| !line.is_empty()) { hub.publish(line); }
+}
+
+/// Publish the lines in the body of a POST request, whose first line is already read.
+fn publish_request(hub: &Mutex<Hub>, mut reader: BufReader<TcpStream>) -> std::io::Result<()>
+{
+    let mut length = 0;
+
+    loop
+    {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 { return Ok(()); }
+        if header.trim().is_empty() { break; }
+
+        if let Some((name, value)) = header.split_once(':')
+        {
+            if name.trim().eq_ignore_ascii_case("content-length") { length = value.trim().parse::<usize>().unwrap_or(0); }
+        }
+    }
+
+    let status = if length > MAX_BODY { "413 Payload Too Large" }
+                 else
+                 {
+                     let mut body = vec![0u8; length];
+                     reader.read_exact(&mut body)?;
+                     publish_lines(hub, &String::from_utf8_lossy(&body));
+                     "204 No Content"
+                 };
+
+    let response = format!("HTTP/1.1 {}\r\nAccess-Control-Allow-Origin: *\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+    reader.get_mut().write_all(response.as_bytes())
+}
+
+/// Tell browsers, sending a batch of lines in each request, from plain TCP clients sending lines for
+/// as long as their game lasts.
+fn publisher(hub: Arc<Mutex<Hub>>, stream: TcpStream)
+{
+    let mut reader = BufReader::new(stream);
+    let mut first = String::new();
+
+    if reader.read_line(&mut first).is_err() { return; }
+
+    if first.starts_with("POST ")
+    {
+        if reader.get_ref().set_read_timeout(Some(Duration::from_secs(10))).is_ok() { let _ = publish_request(&hub, reader); }
+        return;
+    }
+
+    publish_lines(&hub, &first);
+
+    for line in reader.lines()
+    {
+        match line
+        {
+            Ok(line) => publish_lines(&hub, &line),
+            Err(_) => break
+        }
+    }
+}
+
+/// Tell browsers from plain TCP clients, which do not send anything.
+fn subscriber(hub: Arc<Mutex<Hub>>, mut stream: TcpStream) -> std::io::Result<()>
+{
+    let mut request = [0u8; 1024];
+
+    stream.set_read_timeout(Some(Duration::from_millis(300)))?;
+    let http = matches!(stream.read(&mut request), Ok(n) if request[..n].starts_with(b"GET "));
+
+    if http
+    {
+        stream.write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nAccess-Control-Allow-Origin: *\r\n\r\n")?;
+    }
+
+    stream.set_read_timeout(None)?;
+    // Spectators not keeping up are dropped, rather than slowing down the others
+    stream.set_write_timeout(Some(Duration::from_secs(1)))?;
+    let lines = hub.lock().unwrap().subscribe();
+
+    if let Some(lines) = lines { Subscriber { stream, http }.forward(lines); }
+    Ok(())
+}
+
+fn accept(listener: TcpListener, hub: Arc<Mutex<Hub>>, handler: fn(Arc<Mutex<Hub>>, TcpStream))
+{
+    for stream in listener.incoming().flatten()
+    {
+        let hub = hub.clone();
+        thread::spawn(move || handler(hub, stream));
+    }
+}
+
+fn serve(pub_addr: &str, sub_addr: &str) -> Result<(), String>
+{
+    let hub = Arc::new(Mutex::new(Hub::default()));
+    let publishers = TcpListener::bind(pub_addr).map_err(|e| format!("{}: {}", pub_addr, e))?;
+    let subscribers = TcpListener::bind(sub_addr).map_err(|e| format!("{}: {}", sub_addr, e))?;
+
+    println!("Publish at {}, watch at {}", pub_addr, sub_addr);
+
+    let sub_hub = hub.clone();
+    thread::spawn(move || accept(subscribers, sub_hub, |hub, stream| { let _ = subscriber(hub, stream); }));
+    accept(publishers, hub, publisher);
+    Ok(())
+}
+
+fn demo(pub_addr: &str, ticks: i32) -> Result<(), String>
+{
+    let mut stream = TcpStream::connect(pub_addr).map_err(|e| format!("{}: {}", pub_addr, e))?;
+    let mut game = FroggerGame::with_config(GameConfig { seed: 42, ..GameConfig::default() });
+    let mut encoder = StreamEncoder::new();
+    let mut input = InputState::new();
+    let mut rng = Rng::new(7);
+    let moves = [Key::Up, Key::Up, Key::Left, Key::Right, Key::Down];
+
+    while game.ticks() < ticks && !game.game_over()
+    {
+        let presses = if game.ticks() % 8 == 0 { vec![moves[rng.randint(0, 4) as usize]] } else { vec![] };
+        input.update_with_presses(Default::default(), presses);
+        game.tick(&input);
+
+        let line = encoder.encode(&View::of(&game)) + "\n";
+        stream.write_all(line.as_bytes()).map_err(|e| e.to_string())?;
+        thread::sleep(Duration::from_millis(1000 / 30));
+    }
+
+    Ok(())
+}
+
+fn watch(sub_addr: &str) -> Result<(), String>
+{
+    let stream = TcpStream::connect(sub_addr).map_err(|e| format!("{}: {}", sub_addr, e))?;
+    let mut decoder = StreamDecoder::new();
+    let mut bytes = 0;
+
+    for line in BufReader::new(stream).lines()
+    {
+        let line = line.map_err(|e| e.to_string())?;
+        bytes += line.len() + 1;
+
+        if let Some(view) = decoder.decode(&line)?
+        {
+            if view.tick % 30 == 0
+            {
+                println!("tick {} {:?} level {} scores {:?} lives {:?} actors {} ({} bytes received)",
+                         view.tick, view.phase, view.level, view.scores, view.lives, view.actors.len(), bytes);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn main()
+{
+    let args: Vec<String> = env::args().collect();
+    let arg = |i: usize| args.get(i).map(|a| a.as_str());
+
+    let result = match (arg(1), arg(2), arg(3))
+    {
+        (Some("serve"), Some(pub_addr), Some(sub_addr)) => serve(pub_addr, sub_addr),
+        (Some("demo"), Some(pub_addr), ticks) => demo(pub_addr, ticks.and_then(|t| t.parse().ok()).unwrap_or(i32::MAX)),
+        (Some("watch"), Some(sub_addr), _) => watch(sub_addr),
+        _ => Err("Usage: relay serve PUB_ADDR SUB_ADDR | relay demo PUB_ADDR [TICKS] | relay watch SUB_ADDR".to_string())
+    };
+
+    if let Err(e) = result
+    {
+        eprintln!("{}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,201 @@
+use std::collections::HashSet;
+
+use crate::actor::*;
+use crate::frogger::*;
+use crate::input::*;
+use crate::replay::{tick_with_actions, InputSource};
+
+/// Ticks simulated for each move of the search: a whole hop and the tick the frog is checked after
+/// landing (drowning, home slots), or a wait as long.
+const MOVE_TICKS: i32 = HOP_TICKS + 1;
+/// Moves tried from each state, in order of preference when they lead to equally good states.
+const MOVES: [Option<Action>; 5] = [Some(Action::Up), None, Some(Action::Left), Some(Action::Right), Some(Action::Down)];
+/// Value of taking a home slot, above any progress on the board.
+const HOME_VALUE: i32 = 100_000;
+
+/// How well the bot plays.
+#[derive(Copy, Clone, Debug)]
+pub struct BotConfig
+{
+    pub depth: usize,         // Moves searched ahead
+    pub reaction_delay: i32   // Ticks waited after landing before the next move
+}
+impl Default for BotConfig
+{
+    fn default() -> Self { BotConfig::normal() }
+}
+impl BotConfig
+{
+    pub fn easy() -> BotConfig { BotConfig { depth: 2, reaction_delay: 10 } }
+    pub fn normal() -> BotConfig { BotConfig { depth: 3, reaction_delay: 3 } }
+    pub fn hard() -> BotConfig { BotConfig { depth: 5, reaction_delay: 0 } }
+
+    pub fn preset(name: &str) -> Option<BotConfig>
+    {
+        match name.trim().to_lowercase().as_str()
+        {
+            "easy" => Some(BotConfig::easy()),
+            "normal" => Some(BotConfig::normal()),
+            "hard" => Some(BotConfig::hard()),
+            _ => None
+        }
+    }
+}
+
+/// A state reached by the search, with the move it started from.
+struct Node
+{
+    game: FroggerGame,
+    first: Option<Action>,
+    homes: usize
+}
+
+/// Player moving its frog by searching ahead over copies of the game: from the current state it tries
+/// every sequence of moves up to the search depth, dropping the ones losing a life, and takes the
+/// first move of the sequence getting closest to a free home slot.
+pub struct Bot
+{
+    config: BotConfig,
+    player: usize,
+    idle: i32,   // Ticks since the frog is ready to move
+    wait: i32    // Ticks left before searching again, after deciding to stay still
+}
+impl Bot
+{
+    pub fn new(config: BotConfig, player: usize) -> Bot
+    {
+        Bot { config, player, idle: 0, wait: 0 }
+    }
+
+    pub fn config(&self) -> &BotConfig { &self.config }
+    pub fn player(&self) -> usize { self.player }
+
+    fn frog(game: &FroggerGame, player: usize) -> Option<&Frog>
+    {
+        game.frogs().into_iter().find(|hero| hero.player() == player && !hero.out())
+    }
+
+    fn taken(game: &FroggerGame) -> usize { game.homes().iter().filter(|h| h.is_some()).count() }
+
+    /// How good a state is: home slots taken, then rows climbed, then closeness to a free home slot.
+    fn value(&self, node: &Node) -> i32
+    {
+        let hero = match Bot::frog(&node.game, self.player)
+        {
+            Some(hero) => hero,
+            None => return i32::MIN
+        };
+        let centre = hero.pos() + hero.size() / pt(2, 2);
+        let homes = node.game.homes();
+        let distance = home_slots().iter().zip(&homes)
+            .filter(|(_, owner)| owner.is_none())
+            .map(|((pos, size), _)| (pos.x + size.x / 2 - centre.x).abs())
+            .min()
+            .unwrap_or(0);
+
+        node.homes as i32 * HOME_VALUE - centre.y * 16 - distance
+    }
+
+    /// State after making a move, unless the frog loses a life meanwhile. Hops are followed by the
+    /// reaction delay, as the frog cannot move again before.
+    fn advance(&self, node: &Node, first: Option<Action>, action: Option<Action>) -> Option<Node>
+    {
+        let mut game = node.game.clone();
+        let mut input = InputState::new();
+        let lives = game.player_lives(self.player);
+        let ticks = if action.is_some() { MOVE_TICKS + self.config.reaction_delay } else { MOVE_TICKS };
+
+        for t in 0..ticks
+        {
+            let actions: Vec<Action> = if t == 0 { action.into_iter().collect() } else { vec![] };
+            tick_with_actions(&mut game, &mut input, &[(self.player, actions)]);
+
+            let dead = Bot::frog(&game, self.player).is_none_or(|hero| hero.dying());
+            if dead || game.player_lives(self.player) < lives { return None; }
+            if game.phase() != Phase::Playing { break; }
+        }
+
+        let homes = Bot::taken(&game);
+        Some(Node { game, first, homes })
+    }
+
+    /// First move of the best sequence found from the current state; `None` to stay still.
+    pub fn search(&self, game: &FroggerGame) -> Option<Action>
+    {
+        let start = Node { game: game.clone(), first: None, homes: Bot::taken(game) };
+        let mut layer: Vec<Node> = vec![];
+        let mut best: Option<(i32, Option<Action>)> = None;
+
+        for action in MOVES.iter()
+        {
+            layer.extend(self.advance(&start, *action, *action));
+        }
+
+        for depth in 1..=self.config.depth
+        {
+            // Sequences reaching a home slot end there; the other ones are compared at full depth
+            for node in layer.iter().filter(|n| n.homes > start.homes || depth == self.config.depth)
+            {
+                let value = self.value(node);
+                if best.is_none_or(|(v, _)| value > v) { best = Some((value, node.first)); }
+            }
+
+            if best.is_some() || depth == self.config.depth { break; }
+
+            // States reached by different sequences with the frog in the same place are searched once
+            let mut seen: HashSet<(i32, i32)> = HashSet::new();
+            let mut next: Vec<Node> = vec![];
+
+            for node in &layer
+            {
+                for action in MOVES.iter()
+                {
+                    if let Some(child) = self.advance(node, node.first, *action)
+                    {
+                        let pos = Bot::frog(&child.game, self.player).map_or(pt(-1, -1), |hero| hero.pos());
+                        if seen.insert((pos.x, pos.y)) { next.push(child); }
+                    }
+                }
+            }
+
+            if next.is_empty() { break; }
+            layer = next;
+        }
+
+        // With no way to survive the whole search, any surviving move is better than none
+        match best
+        {
+            Some((_, first)) => first,
+            None => layer.iter().max_by_key(|n| self.value(n)).and_then(|n| n.first)
+        }
+    }
+}
+impl InputSource for Bot
+{
+    fn actions(&mut self, game: &FroggerGame) -> Vec<(usize, Vec<Action>)>
+    {
+        let moving = game.phase() == Phase::Playing && (game.simultaneous() || game.turn() == self.player);
+        let ready = moving && Bot::frog(game, self.player).is_some_and(|hero| !hero.hopping() && !hero.dying());
+
+        if !ready
+        {
+            self.idle = 0;
+            return vec![];
+        }
+
+        self.idle += 1;
+        self.wait = (self.wait - 1).max(0);
+
+        if self.idle <= self.config.reaction_delay || self.wait > 0 { return vec![]; }
+
+        match self.search(game)
+        {
+            Some(action) => vec![(self.player, vec![action])],
+            None =>
+            {
+                self.wait = MOVE_TICKS;
+                vec![]
+            }
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use crate::actor::*;
+use crate::frogger::*;
+use crate::input::*;
+use crate::replay::tick_with_actions;
+use crate::stream::View;
+
+/// Height of the rows the frog hops across, in pixels.
+const ROW_HEIGHT: i32 = 32;
+
+/// Moves available to an agent at each step.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum EnvAction
+{
+    Noop,
+    Up,
+    Down,
+    Left,
+    Right
+}
+impl EnvAction
+{
+    /// The action space, indexed as agents usually pick actions.
+    pub const ALL: [EnvAction; 5] = [EnvAction::Noop, EnvAction::Up, EnvAction::Down, EnvAction::Left, EnvAction::Right];
+
+    pub fn from_index(index: usize) -> Option<EnvAction> { EnvAction::ALL.get(index).copied() }
+
+    fn action(&self) -> Option<Action>
+    {
+        match self
+        {
+            EnvAction::Noop => None,
+            EnvAction::Up => Some(Action::Up),
+            EnvAction::Down => Some(Action::Down),
+            EnvAction::Left => Some(Action::Left),
+            EnvAction::Right => Some(Action::Right)
+        }
+    }
+}
+
+/// Weights of the events making up the reward of a step.
+#[derive(Copy, Clone, Debug)]
+pub struct Rewards
+{
+    pub progress: f64,   // For each row the frog gets closer to the homes than before in its life
+    pub home: f64,       // For each home slot taken
+    pub death: f64,      // For each life lost
+    pub time: f64        // For each tick played
+}
+impl Default for Rewards
+{
+    fn default() -> Self
+    {
+        Rewards { progress: 1.0, home: 10.0, death: -10.0, time: -0.01 }
+    }
+}
+
+/// Options an environment is created with.
+#[derive(Copy, Clone, Debug)]
+pub struct EnvConfig
+{
+    pub game: GameConfig,        // The seed is replaced at each reset; games have a single player
+    pub rewards: Rewards,
+    pub frame_skip: i32,         // Ticks played at each step; the action is pressed during the first one
+    pub max_steps: Option<i32>   // Steps after which an episode is over, even if the game is not
+}
+impl Default for EnvConfig
+{
+    fn default() -> Self
+    {
+        EnvConfig { game: GameConfig::default(), rewards: Rewards::default(), frame_skip: 4, max_steps: None }
+    }
+}
+
+/// What happened during a step, besides the reward.
+#[derive(Clone, Debug, Default)]
+pub struct StepInfo
+{
+    pub score: i32,
+    pub lives: i32,
+    pub level: i32,
+    pub ticks: i32,
+    pub progress: i32,              // Rows gained during the step
+    pub homes: i32,                 // Home slots taken during the step
+    pub deaths: Vec<DeathCause>,    // Lives lost during the step
+    pub won: bool,
+    pub truncated: bool             // Whether the episode ended on `max_steps` rather than with the game
+}
+
+/// Reinforcement learning environment around a single player game, in the style of Gym: `reset` starts
+/// an episode, then `step` plays an action and reports what followed, until `done`.
+pub struct Env
+{
+    config: EnvConfig,
+    game: FroggerGame,
+    input: InputState,
+    steps: i32,
+    best_row: Option<i32>,
+    done: bool
+}
+impl Env
+{
+    pub fn new(config: EnvConfig) -> Env
+    {
+        let mut config = config;
+        config.game.players = 1;
+        config.frame_skip = config.frame_skip.max(1);
+
+        Env { config,
+              game: FroggerGame::with_config(config.game),
+              input: InputState::new(),
+              steps: 0,
+              best_row: None,
+              done: false }
+    }
+
+    pub fn config(&self) -> &EnvConfig { &self.config }
+    pub fn game(&self) -> &FroggerGame { &self.game }
+    pub fn steps(&self) -> i32 { self.steps }
+    pub fn done(&self) -> bool { self.done }
+
+    /// Start a new episode with a game built from the given seed, returning the first observation.
+    pub fn reset(&mut self, seed: u32) -> View
+    {
+        self.config.game.seed = seed;
+        self.game = FroggerGame::with_config(self.config.game);
+        self.input = InputState::new();
+        self.steps = 0;
+        self.best_row = None;
+        self.done = false;
+
+        View::of(&self.game)
+    }
+
+    /// Row of the frog, when sitting on the board and alive.
+    fn frog_row(&self) -> Option<i32>
+    {
+        self.game.frogs().into_iter()
+            .find(|hero| !hero.dying() && !hero.hopping() && !hero.out())
+            .map(|hero| (hero.pos().y + hero.size().y / 2) / ROW_HEIGHT)
+    }
+
+    /// Play an action for `frame_skip` ticks, returning the observation, the reward, whether the
+    /// episode is over and what happened. Steps after the end of an episode do nothing.
+    pub fn step(&mut self, action: EnvAction) -> (View, f64, bool, StepInfo)
+    {
+        let mut info = StepInfo::default();
+        let mut ticks = 0;
+
+        if !self.done
+        {
+            let deaths = self.game.stats().deaths().len();
+            let homes = self.game.homes();
+
+            for skip in 0..self.config.frame_skip
+            {
+                let (died, taken) = (self.game.stats().deaths().len(), self.game.homes());
+                let actions: Vec<Action> = if skip == 0 { action.action().into_iter().collect() } else { vec![] };
+
+                tick_with_actions(&mut self.game, &mut self.input, &[(0, actions)]);
+                ticks += 1;
+
+                // Progress counts again from the starting row after each death or home
+                if self.game.stats().deaths().len() > died || self.game.homes() != taken { self.best_row = None; }
+
+                if let Some(row) = self.frog_row()
+                {
+                    let best = self.best_row.unwrap_or(row);
+                    info.progress += (best - row).max(0);
+                    self.best_row = Some(best.min(row));
+                }
+
+                if self.game.game_over() || self.game.game_won() { break; }
+            }
+
+            info.deaths = self.game.stats().deaths()[deaths..].iter().map(|d| d.cause).collect();
+            info.homes = self.game.homes().iter().zip(&homes).filter(|(new, old)| new.is_some() && old.is_none()).count() as i32;
+            self.steps += 1;
+        }
+
+        info.score = self.game.score();
+        info.lives = self.game.remaining_lives();
+        info.level = self.game.level();
+        info.ticks = self.game.ticks();
+        info.won = self.game.game_won();
+        info.truncated = !self.game.game_over() && !info.won && self.config.max_steps.is_some_and(|max| self.steps >= max);
+        self.done = self.game.game_over() || info.won || info.truncated;
+
+        let r = &self.config.rewards;
+        let reward = r.progress * info.progress as f64 + r.home * info.homes as f64 + r.death * info.deaths.len() as f64
+                     + r.time * ticks as f64;
+
+        (View::of(&self.game), reward, self.done, info)
+    }
+}
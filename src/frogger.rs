@@ -1,10 +1,153 @@
 use std::any::Any;
 use std::cmp::{min, max};
+use std::collections::VecDeque;
 use std::ops::{Add, Sub};
 
 use crate::actor::*;
+use crate::audio::Sound;
+use crate::input::*;
 use crate::rand::*;
 
+/// Number of ticks needed by the frog to complete a single hop.
+pub const HOP_TICKS: i32 = 4;
+/// Number of hops that can be queued, pressing keys faster than the frog can jump.
+const HOP_BUFFER: usize = 2;
+/// Number of ticks each frame of a death sequence stays on screen.
+const DEATH_FRAME_TICKS: i32 = 10;
+/// Number of ticks the frog has to reach a home slot before timing out (60 seconds).
+const LIFE_TICKS: i32 = 60 * 30;
+/// Ticks left to the frog when the time warning sounds, as its time bar turns red.
+const TIME_WARNING_TICKS: i32 = LIFE_TICKS / 5;
+
+/// Number of ticks the board stays still before a level starts.
+const READY_TICKS: i32 = 60;
+/// Number of ticks the cleared level is shown before moving to the next one.
+const LEVEL_CLEAR_TICKS: i32 = 90;
+/// Lives of each player at the beginning of the game.
+const START_LIVES: i32 = 3;
+/// A life more each time the score of a player goes past a multiple of this.
+pub const EXTRA_LIFE_SCORE: i32 = 10_000;
+/// Number of levels to clear to win the game.
+pub const LEVELS: i32 = 5;
+/// Most vehicles and most rafts for each row of the board in a game checked by `GameConfig::check`.
+pub const MAX_VEHICLES_PER_ROW: i32 = 8;
+pub const MAX_RAFTS_PER_ROW: i32 = 4;
+
+/// Starting positions of the frogs: one in the middle, or one for each player playing at the same time.
+const START_POS: Pt = pt(308, 440);
+const START_POS_SIMULTANEOUS: [Pt; 2] = [pt(244, 440), pt(372, 440)];
+/// Offset of the sprites of the second player frog, in a different colour.
+const SKIN_OFFSET: Pt = pt(0, 64);
+
+/// Left side of the five home slots.
+const HOME_SLOTS_X: [i32; 5] = [48, 176, 304, 432, 560];
+/// Frogs whose centre goes above this line, outside a home slot, hit the river bank.
+const RIVER_BANK_Y: i32 = 80;
+
+// Death sequences frames: (sprite, size)
+const SKULL: (Pt, Pt) = (pt(2, 192), pt(30, 32));
+const SPLASH_SMALL: (Pt, Pt) = (pt(198, 163), pt(20, 22));
+const SPLASH_BIG: (Pt, Pt) = (pt(226, 162), pt(28, 26));
+const SQUASH_1: (Pt, Pt) = (pt(228, 194), pt(24, 28));
+const SQUASH_2: (Pt, Pt) = (pt(258, 194), pt(28, 26));
+const SQUASH_3: (Pt, Pt) = (pt(288, 192), pt(32, 32));
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ActorKind
+{
+    Frog,
+    Vehicle,
+    Raft,
+    Turtle,
+    Crocodile,
+    Water,
+    Other
+}
+impl ActorKind
+{
+    pub const ALL: [ActorKind; 7] = [ActorKind::Frog, ActorKind::Vehicle, ActorKind::Raft, ActorKind::Turtle, 
+                                     ActorKind::Crocodile, ActorKind::Water, ActorKind::Other];
+
+    pub fn of(actor: &dyn Actor) -> ActorKind
+    {
+        let any = actor.as_any();
+
+        if any.is::<Frog>() { ActorKind::Frog }
+        else if any.is::<Vehicle>() { ActorKind::Vehicle }
+        else if any.is::<Raft>() { ActorKind::Raft }
+        else if any.is::<Turtle>() { ActorKind::Turtle }
+        else if any.is::<Crocodile>() { ActorKind::Crocodile }
+        else if any.is::<Water>() { ActorKind::Water }
+        else { ActorKind::Other }
+    }
+}
+
+/// Trigger zones (position, size) of the home slots, from left to right.
+pub fn home_slots() -> Vec<(Pt, Pt)>
+{
+    HOME_SLOTS_X.iter().map(|x| (pt(*x, 58), pt(32, RIVER_BANK_Y - 58))).collect()
+}
+
+/// Whether adding the points to the score goes past a multiple of `EXTRA_LIFE_SCORE`.
+fn extra_life(score: i32, points: i32) -> bool
+{
+    (score + points) / EXTRA_LIFE_SCORE > score / EXTRA_LIFE_SCORE
+}
+
+/// Sprite and size of a frog sitting in a home slot taken by the given player.
+pub fn win_sprite(player: usize) -> (Pt, Pt)
+{
+    if player % 2 == 1 { (pt(68, 6) + SKIN_OFFSET, pt(24, 18)) } else { (pt(68, 134), pt(24, 18)) }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DeathCause
+{
+    Squashed,    // Hit by a vehicle
+    Drowned,     // Landed in water, or on an immersed turtle
+    Eaten,       // Touched a crocodile
+    TimedOut,    // The life timer expired
+    MissedHome   // Landed on the river bank or on a slot already taken
+}
+impl DeathCause
+{
+    pub const ALL: [DeathCause; 5] = [DeathCause::Squashed, DeathCause::Drowned, DeathCause::Eaten, DeathCause::TimedOut, DeathCause::MissedHome];
+
+    /// Frames played at the death position before respawning.
+    pub fn frames(&self) -> &'static [(Pt, Pt)]
+    {
+        match self
+        {
+            DeathCause::Squashed => &[SQUASH_1, SQUASH_2, SQUASH_3, SKULL],
+            DeathCause::Drowned => &[SPLASH_SMALL, SPLASH_BIG, SPLASH_SMALL, SKULL],
+            DeathCause::Eaten => &[SQUASH_3, SPLASH_BIG, SKULL],
+            DeathCause::TimedOut => &[SKULL, SKULL],
+            DeathCause::MissedHome => &[SPLASH_BIG, SKULL]
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct Death
+{
+    pub cause: DeathCause,
+    pub pos: Pt,
+    pub tick: i32,
+    pub player: usize
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct GameStats
+{
+    deaths: Vec<Death>
+}
+impl GameStats
+{
+    pub fn deaths(&self) -> &Vec<Death> { &self.deaths }
+    pub fn deaths_by(&self, cause: DeathCause) -> usize { self.deaths.iter().filter(|d| d.cause == cause).count() }
+}
+
+#[derive(Clone)]
 pub struct Vehicle 
 {
     pos: Pt,
@@ -14,9 +157,9 @@ pub struct Vehicle
 }
 impl Vehicle 
 {
-    pub fn new(pos: Pt, speed: i32) -> Vehicle 
+    pub fn new(pos: Pt, speed: i32, rng: &mut Rng) -> Vehicle 
     {
-        let aspect = randint(0, 2);
+        let aspect = rng.randint(0, 2);
         let size = if aspect !=2 { pt(32, 26) } else { pt(62, 24) };
 
         let sprite = if aspect == 0 && speed >= 0 { pt(192, 4) }        // Yellow vehicle sprite
@@ -45,8 +188,10 @@ impl Actor for Vehicle
     fn sprite(&self) -> Option<Pt> { Some(self.sprite) } 
     fn alive(&self) -> bool { true }
     fn as_any(&self) -> &dyn Any { self }
+    fn clone_dyn(&self) -> Box<dyn Actor> { Box::new(self.clone()) }
 }
 
+#[derive(Clone)]
 pub struct Raft 
 {
     pos: Pt,
@@ -77,9 +222,11 @@ impl Actor for Raft
     fn size(&self) -> Pt { self.size }
     fn sprite(&self) -> Option<Pt> { Some(pt(192, 102)) }
     fn alive(&self) -> bool { true }
-    fn as_any(&self) -> &dyn Any { self }    
+    fn as_any(&self) -> &dyn Any { self }
+    fn clone_dyn(&self) -> Box<dyn Actor> { Box::new(self.clone()) }
 }
 
+#[derive(Clone)]
 pub struct Turtle
 {
     pos: Pt,
@@ -95,6 +242,9 @@ impl Turtle
     {
         Turtle { pos: pos, sprite: pt(194, 134), size: pt(26, 18), speed: speed, immersed: false, counter: 0 }
     }
+
+    /// Whether the turtle is under water: frogs on it drown.
+    pub fn immersed(&self) -> bool { self.immersed }
 }
 impl Actor for Turtle 
 {
@@ -103,7 +253,7 @@ impl Actor for Turtle
         let scr = arena.size();
 
         self.pos.x = self.pos.x + self.speed;
-        self.counter = if randint(0, 1000) == 0 { 20 } else { self.counter };  // Prob 1:1000 to begin immersion
+        self.counter = if arena.randint(0, 1000) == 0 { 20 } else { self.counter };  // Prob 1:1000 to begin immersion
 
         if self.counter < 10  // Swim right
         {
@@ -164,8 +314,10 @@ impl Actor for Turtle
     fn sprite(&self) -> Option<Pt> { Some(self.sprite) }
     fn alive(&self) -> bool { true }
     fn as_any(&self) -> &dyn Any { self }
+    fn clone_dyn(&self) -> Box<dyn Actor> { Box::new(self.clone()) }
 }
 
+#[derive(Clone)]
 pub struct Crocodile
 {
     pos: Pt,
@@ -211,8 +363,10 @@ impl Actor for Crocodile
     fn sprite(&self) -> Option<Pt> { Some(self.sprite) }
     fn alive(&self) -> bool { true }
     fn as_any(&self) -> &dyn Any { self }
+    fn clone_dyn(&self) -> Box<dyn Actor> { Box::new(self.clone()) }
 }
 
+#[derive(Clone)]
 pub struct Water
 {
     pos: Pt,
@@ -233,8 +387,10 @@ impl Actor for Water
     fn sprite(&self) -> Option<Pt> { None }
     fn alive(&self) -> bool { true }
     fn as_any(&self) -> &dyn Any { self }
+    fn clone_dyn(&self) -> Box<dyn Actor> { Box::new(self.clone()) }
 }
 
+#[derive(Clone)]
 pub struct Frog
 {
     pos: Pt,
@@ -246,11 +402,26 @@ pub struct Frog
     sprite: Pt,
     starting_sprite: Pt,
     lives: i32,
+    score: i32,
+    furthest_row: i32,
     blinking: i32,
+    time_left: i32,
+    death: Option<DeathCause>,
+    dying: i32,
+    died: Option<DeathCause>,
+    hopped: bool,    // Whether a hop started during the last tick
+    extra_life: bool,   // Whether the score earned a life during the last tick
+    hop: i32,
+    hop_dir: Pt,
+    hop_queue: VecDeque<Pt>,
     in_water: bool,
     wins: Vec<bool>,
     win_sprite: Pt,
-    win_sprite_size: Pt
+    win_sprite_size: Pt,
+    player: usize,
+    controls: usize,
+    skin: Pt,
+    stays: bool
 }
 impl Frog
 {
@@ -259,6 +430,7 @@ impl Frog
         let starting_size = pt(24, 18);
         let starting_sprite = pt(68, 6);
         let mut wins: Vec<bool> = vec![];
+        let (win_sprite, win_sprite_size) = win_sprite(0);
 
         for _ in 0..5 { wins.push(false); }
 
@@ -270,34 +442,170 @@ impl Frog
                speed: 32,
                sprite: starting_sprite,
                starting_sprite: starting_sprite,
-               lives: 3,
+               lives: START_LIVES,
+               score: 0,
+               furthest_row: Frog::row(pos, starting_size),
                blinking: 0,
+               time_left: LIFE_TICKS,
+               death: None,
+               dying: 0,
+               died: None,
+               hopped: false,
+               extra_life: false,
+               hop: 0,
+               hop_dir: pt(0, 0),
+               hop_queue: VecDeque::new(),
                in_water: false,
                wins: wins,
-               win_sprite: pt(68, 134),
-               win_sprite_size: pt(24, 18) }
+               win_sprite,
+               win_sprite_size,
+               player: 0,
+               controls: 0,
+               skin: pt(0, 0),
+               stays: false }
+    }
+
+    /// Frog of the given player, moved by the keys bound for the `controls` player.
+    /// The frogs of the second player are drawn in a different colour.
+    pub fn with_player(pos: Pt, player: usize, controls: usize) -> Frog
+    {
+        let mut frog = Frog::new(pos);
+        frog.player = player;
+        frog.controls = controls;
+
+        if player % 2 == 1
+        {
+            frog.skin = SKIN_OFFSET;
+            frog.sprite = frog.sprite + SKIN_OFFSET;
+            frog.starting_sprite = frog.starting_sprite + SKIN_OFFSET;
+            frog.win_sprite = win_sprite(player).0;
+        }
+        frog
     }
 
     pub fn get_wins(&self) -> &Vec<bool> { &self.wins }
     pub fn get_win_sprite(&self) -> Pt { self.win_sprite }
     pub fn get_win_sprite_size(&self) -> Pt { self.win_sprite_size }
 
+    pub fn hopping(&self) -> bool { self.hop > 0 }
+
+    /// Sprite and size of the frog while jumping and while sitting, for the given hop direction.
+    fn hop_frames(dir: Pt) -> ((Pt, Pt), (Pt, Pt))
+    {
+        if dir.x < 0 { ((pt(132, 6), pt(26, 20)), (pt(166, 4), pt(18, 24))) }         // Left
+        else if dir.x > 0 { ((pt(32, 38), pt(26, 20)), (pt(6, 36), pt(18, 24))) }     // Right
+        else if dir.y > 0 { ((pt(132, 34), pt(20, 26)), (pt(98, 40), pt(24, 18))) }   // Down
+        else { ((pt(38, 4), pt(20, 28)), (pt(68, 6), pt(24, 18))) }                   // Up
+    }
+
+    fn start_hop(&mut self, dir: Pt)
+    {
+        let ((sprite, size), _) = Frog::hop_frames(dir);
+
+        self.hop = HOP_TICKS;
+        self.hop_dir = dir;
+        self.hopped = true;
+        self.sprite = sprite + self.skin;
+        self.size = size;
+    }
+
+    pub fn lives(&self) -> i32 { self.lives }
+    pub fn score(&self) -> i32 { self.score }
+    pub fn player(&self) -> usize { self.player }
+
+    /// Whether the frog lost its last life, and only stays on the board to keep its home slots.
+    pub fn out(&self) -> bool { self.lives <= 0 && !self.dying() }
+
+    /// Position the frog will land at, at the end of the current hop.
+    fn destination(&self) -> Pt
+    {
+        let dist = self.speed / HOP_TICKS * self.hop;
+        self.pos + self.hop_dir * pt(dist, dist)
+    }
+
+    /// Index of the 32 pixels high row the centre of the frog is in.
+    fn row(pos: Pt, size: Pt) -> i32 { (pos.y + size.y / 2) / 32 }
+    pub fn time_left(&self) -> i32 { self.time_left }
+    pub fn dying(&self) -> bool { self.death.is_some() }
+
+    /// State of the frog its sprite and position do not show: timers, the hop in progress and the
+    /// queued hops, to compare games simulated apart.
+    pub fn hidden_state(&self) -> Vec<i32>
+    {
+        let mut values = vec![self.time_left, self.blinking, self.dying, self.furthest_row, self.in_water as i32,
+                              self.hop, self.hop_dir.x, self.hop_dir.y, self.hop_queue.len() as i32];
+
+        values.extend(self.hop_queue.iter().flat_map(|dir| vec![dir.x, dir.y]));
+        values
+    }
+
+    /// Cause of the death happened during the last tick, if any.
+    pub fn died(&self) -> Option<DeathCause> { self.died }
+
     fn reset_position(&mut self)
     {
         self.pos = self.starting_pos;
         self.size = self.starting_size;
         self.sprite = self.starting_sprite;
+        self.hop = 0;
+        self.hop_queue.clear();
+        self.time_left = LIFE_TICKS;
+        self.furthest_row = Frog::row(self.pos, self.size);
         self.lives += 1;
     }
 
-    fn lose_life(&mut self)
+    /// Add points to the score, with a life more when it goes past a multiple of `EXTRA_LIFE_SCORE`.
+    fn add_score(&mut self, points: i32)
+    {
+        if extra_life(self.score, points)
+        {
+            self.lives += 1;
+            self.extra_life = true;
+        }
+        self.score += points;
+    }
+
+    fn lose_life(&mut self, cause: DeathCause)
     {
-        self.blinking = 60;
+        if self.dying() { return; }  // Only the first hit counts
+
+        let (sprite, size) = cause.frames()[0];
+
+        self.death = Some(cause);
+        self.died = Some(cause);
+        self.dying = 0;
+        self.blinking = 0;
+        self.hop = 0;
+        self.hop_queue.clear();
         self.in_water = false;
         self.lives = self.lives - 1;
-        self.pos = self.starting_pos;
-        self.size = self.starting_size;
-        self.sprite = self.starting_sprite;
+        self.sprite = sprite;
+        self.size = size;
+    }
+
+    /// Advance the death sequence, respawning the frog at the starting position when it is over.
+    fn play_death(&mut self, cause: DeathCause)
+    {
+        let frames = cause.frames();
+
+        self.dying += 1;
+
+        if self.dying < frames.len() as i32 * DEATH_FRAME_TICKS
+        {
+            let (sprite, size) = frames[(self.dying / DEATH_FRAME_TICKS) as usize];
+            self.sprite = sprite;
+            self.size = size;
+        }
+        else
+        {
+            self.death = None;
+            self.blinking = 60;
+            self.time_left = LIFE_TICKS;
+            self.pos = self.starting_pos;
+            self.size = self.starting_size;
+            self.sprite = self.starting_sprite;
+            self.furthest_row = Frog::row(self.pos, self.size);
+        }
     }
 }
 impl Actor for Frog
@@ -305,18 +613,46 @@ impl Actor for Frog
     fn act(&mut self, arena: &mut ArenaStatus) 
     {
         let scr = arena.size();
-        let current_keys = arena.current_keys();
-        let previous_keys = arena.previous_keys();
+        let input = arena.input();
 
         self.step = pt(0, 0);
+        self.died = None;
+        self.hopped = false;
+        self.extra_life = false;
+
+        if let Some(cause) = self.death
+        {
+            self.play_death(cause);
+            return;
+        }
+
+        if self.out() { return; }
+
+        // Home slots already taken by any frog, and places where the other frogs are or are hopping to
+        let mut taken = self.wins.clone();
+        let mut blocked: Vec<Pt> = vec![];
+
+        for other in arena.collisions().iter().chain(arena.others())
+        {
+            if let Some(frog) = other.as_any().downcast_ref::<Frog>()
+            {
+                for (t, won) in taken.iter_mut().zip(&frog.wins) { *t = *t || *won; }
+                if !frog.out() && !frog.dying() { blocked.extend([frog.pos, frog.destination()].iter()); }
+            }
+        }
+
+        self.time_left = max(self.time_left - 1, 0);
+        if self.time_left == 0 { self.lose_life(DeathCause::TimedOut); }
 
         if self.blinking == 0
         {
+            self.in_water = false;
+
             for other in arena.collisions()
             {
                 if let Some(_) = other.as_any().downcast_ref::<Vehicle>()
                 {
-                    self.lose_life();
+                    self.lose_life(DeathCause::Squashed);
                 }
                 else if let Some(_) = other.as_any().downcast_ref::<Water>() 
                 {
@@ -329,7 +665,7 @@ impl Actor for Frog
                 }
                 else if let Some(_) = other.as_any().downcast_ref::<Crocodile>()
                 {
-                    self.lose_life();
+                    self.lose_life(DeathCause::Eaten);
                 }
                 else if let Some(turtle) = other.as_any().downcast_ref::<Turtle>()
                 {
@@ -345,114 +681,81 @@ impl Actor for Frog
                 }
             }
 
-            // Check whatever it is colliding with water
-            if self.in_water { self.lose_life(); }
+            // Check whatever it is colliding with water: while jumping the frog is still in the air
+            if self.in_water && !self.hopping() { self.lose_life(DeathCause::Drowned); }
         }
 
-        // Check win, from left slot to right slot.
-        if self.pos.x + (self.size.x / 2) > 48 && self.pos.x + (self.size.x / 2) < 80 && self.pos.y + (self.size.y / 2) > 58 && self.pos.y + (self.size.y / 2) < 80
+        // Check win, from left slot to right slot. Only a landed frog can reach a slot.
+        if !self.hopping() && !self.dying()
         {
-            if !self.wins[0]
-            {
-                self.wins[0] = true;
-                self.reset_position();
-            }
-            else 
-            {
-                self.lose_life();    
-            }
-        }
-        else if self.pos.x + (self.size.x / 2) > 176 && self.pos.x + (self.size.x / 2) < 208 && self.pos.y + (self.size.y / 2) > 58 && self.pos.y + (self.size.y / 2) < 80
-        {
-            if !self.wins[1]
-            {
-                self.wins[1] = true;
-                self.reset_position();
-            }
-            else 
-            {
-                self.lose_life();    
-            }
-        }
-        else if self.pos.x + (self.size.x / 2) > 304 && self.pos.x + (self.size.x / 2) < 336 && self.pos.y + (self.size.y / 2) > 58 && self.pos.y + (self.size.y / 2) < 80
-        {
-            if !self.wins[2]
-            {
-                self.wins[2] = true;
-                self.reset_position();
-            }
-            else 
+            let centre = self.pos + self.size / pt(2, 2);
+            let slot = home_slots().iter().position(|(pos, size)| pos.x < centre.x && centre.x < pos.x + size.x 
+                                                                   && pos.y < centre.y && centre.y < pos.y + size.y);
+
+            match slot
             {
-                self.lose_life();    
+                Some(i) if !taken[i] => 
+                {
+                    // 50 points for the slot, plus 10 for each second left
+                    self.add_score(50 + 10 * (self.time_left / 30));
+                    self.wins[i] = true;
+                    self.reset_position();
+                }
+                Some(_) => self.lose_life(DeathCause::MissedHome),
+                None if centre.y < RIVER_BANK_Y => self.lose_life(DeathCause::MissedHome),
+                None => { }
             }
         }
-        else if self.pos.x + (self.size.x / 2) > 432 && self.pos.x + (self.size.x / 2) < 464 && self.pos.y + (self.size.y / 2) > 58 && self.pos.y + (self.size.y / 2) < 80
+
+        if self.dying() { return; }
+
+        // Hops are queued in the order they were pressed, one direction each: never diagonally.
+        // Keys pressed mid-air are performed as soon as the frog lands, if the queue is not full.
+        for dir in input.player_actions_just_pressed(self.controls).iter().filter_map(|a| a.direction())
         {
-            if !self.wins[3]
-            {
-                self.wins[3] = true;
-                self.reset_position();
-            }
-            else 
-            {
-                self.lose_life();    
-            }
+            if self.hop_queue.len() < HOP_BUFFER { self.hop_queue.push_back(dir); }
         }
-        else if self.pos.x + (self.size.x / 2) > 560 && self.pos.x + (self.size.x / 2) < 592 && self.pos.y + (self.size.y / 2) > 58 && self.pos.y + (self.size.y / 2) < 80
+
+        if !self.hopping()
         {
-            if !self.wins[4]
-            {
-                self.wins[4] = true;
-                self.reset_position();
-            }
-            else 
-            {
-                self.lose_life();    
+            if let Some(dir) = self.hop_queue.pop_front() 
+            { 
+                // Frogs block each other: hops onto another frog are dropped
+                let target = self.pos + dir * pt(self.speed, self.speed);
+                let size = self.starting_size;
+
+                if !blocked.iter().any(|p| (p.x - target.x).abs() < size.x && (p.y - target.y).abs() < size.y) { self.start_hop(dir); }
             }
         }
-        else if self.pos.y + (self.size.y / 2) < 80
-        {
-            self.lose_life();
-        }
 
-        if current_keys.contains(&"ArrowUp") && !previous_keys.contains(&"ArrowUp")
-        {
-            // Change sprite
-            self.size = pt(24, 18);
-            self.sprite = pt(68, 6);
-            // Update the step to be done
-            self.step.y = - self.speed;
-        }
-        else if current_keys.contains(&"ArrowDown") && !previous_keys.contains(&"ArrowDown")
-        {
-            // Change sprite
-            self.size = pt(24, 18);
-            self.sprite = pt(98, 40);
-            // Update the step to be done
-            self.step.y = self.speed;
-        }
+        let mut landed = false;
 
-        if current_keys.contains(&"ArrowLeft") && !previous_keys.contains(&"ArrowLeft")
-        {
-            // Change sprite
-            self.size = pt(18, 24);
-            self.sprite = pt(166, 4);
-            // Update the step to be done
-            self.step.x = -self.speed;
-        }
-        else if current_keys.contains(&"ArrowRight") && !previous_keys.contains(&"ArrowRight")
+        if self.hopping()
         {
-            // Change sprite
-            self.size = pt(18, 24);
-            self.sprite = pt(6, 36);
-            // Update the step to be done
-            self.step.x = self.speed;
+            // Interpolate the position along the hop
+            self.step = self.step + self.hop_dir * pt(self.speed / HOP_TICKS, self.speed / HOP_TICKS);
+            self.hop -= 1;
+
+            if !self.hopping()
+            {
+                let (_, (sprite, size)) = Frog::hop_frames(self.hop_dir);
+                self.sprite = sprite + self.skin;
+                self.size = size;
+                landed = true;
+            }
         }
 
         self.pos = self.pos + self.step;
 
         self.pos.x = min(max(self.pos.x, 0), scr.x - self.size.x);  // clamp the x-val to arena dimension
         self.pos.y = min(max(self.pos.y, 0), scr.y - self.size.y);  // clamp the y-val to arena dimension
+
+        // 10 points for each row never reached before during this life
+        if landed && Frog::row(self.pos, self.size) < self.furthest_row
+        {
+            self.add_score(10);
+            self.furthest_row = Frog::row(self.pos, self.size);
+        }
         self.blinking = max(self.blinking - 1, 0);
     }
 
@@ -461,58 +764,209 @@ impl Actor for Frog
 
     fn sprite(&self) -> Option<Pt> 
     { 
-        if self.blinking > 0 && (self.blinking / 2) % 2 == 0 { None }
+        if self.out() || (self.blinking > 0 && (self.blinking / 2) % 2 == 0) { None }
         else { Some(self.sprite) }
     }
 
-    fn alive(&self) -> bool { self.lives > 0 }
+    fn alive(&self) -> bool { self.lives > 0 || self.dying() || self.stays }
     fn as_any(&self) -> &dyn Any { self }
+    fn clone_dyn(&self) -> Box<dyn Actor> { Box::new(self.clone()) }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Phase
+{
+    Ready,       // Level about to start, the board is still
+    Playing,
+    Dying,       // The frog is playing its death sequence
+    LevelClear,  // All home slots taken, waiting for the next level
+    GameOver,
+    Victory      // Last level cleared
+}
+impl Phase
+{
+    pub const ALL: [Phase; 6] = [Phase::Ready, Phase::Playing, Phase::Dying, Phase::LevelClear, Phase::GameOver, Phase::Victory];
+}
+
+/// How the players of a multiplayer game share the board.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Mode
+{
+    Turns,  // One frog at a time, the players take turns at each death
+    Coop,   // One frog each at the same time, filling the home slots together
+    Versus  // One frog each at the same time, competing for the home slots
+}
+impl Mode
+{
+    pub const ALL: [Mode; 3] = [Mode::Turns, Mode::Coop, Mode::Versus];
+
+    pub fn from_name(name: &str) -> Option<Mode>
+    {
+        Mode::ALL.iter().copied().find(|m| format!("{:?}", m).eq_ignore_ascii_case(name.trim()))
+    }
+}
+
+/// Options a game is created with.
+#[derive(Copy, Clone, Debug)]
+pub struct GameConfig
+{
+    pub size: Pt,
+    pub seed: u32,
+    pub level: i32,  // Starting level
+    pub n_vehicle_per_row: i32,
+    pub n_raft_per_row: i32,
+    pub players: usize,  // At most two when playing at the same time
+    pub mode: Mode
 }
+impl Default for GameConfig
+{
+    fn default() -> Self 
+    {
+        GameConfig { size: pt(640, 480), seed: 1, level: 1, n_vehicle_per_row: 5, n_raft_per_row: 2, players: 1, mode: Mode::Turns }
+    }
+}
+impl GameConfig
+{
+    /// Whether the frogs of all the players are on the board at the same time.
+    pub fn simultaneous(&self) -> bool { self.players > 1 && self.mode != Mode::Turns }
 
+    /// Check that the game can be played within reasonable resources, before creating games with
+    /// options coming from someone else: at most two players, on the board of the default size, with a
+    /// few vehicles and rafts for each row, starting at one of the levels of the game.
+    pub fn check(&self) -> Result<(), String>
+    {
+        let board = GameConfig::default().size;
+
+        if !(1..=MAX_PLAYERS).contains(&self.players) { return Err(format!("Bad number of players: {}", self.players)); }
+        if (self.size.x, self.size.y) != (board.x, board.y) { return Err(format!("Bad board size: {} {}", self.size.x, self.size.y)); }
+        if !(0..=MAX_VEHICLES_PER_ROW).contains(&self.n_vehicle_per_row) { return Err(format!("Bad number of vehicles: {}", self.n_vehicle_per_row)); }
+        if !(0..=MAX_RAFTS_PER_ROW).contains(&self.n_raft_per_row) { return Err(format!("Bad number of rafts: {}", self.n_raft_per_row)); }
+        if !(1..=LEVELS).contains(&self.level) { return Err(format!("Bad level: {}", self.level)); }
+        Ok(())
+    }
+}
+
+/// State of a player, saved while the other one is playing, or at each tick when playing at the same time.
+#[derive(Clone, Debug)]
+pub struct PlayerState
+{
+    pub lives: i32,
+    pub score: i32,
+    pub wins: Vec<bool>,
+    pub level: i32
+}
+impl PlayerState
+{
+    fn new(level: i32) -> PlayerState
+    {
+        PlayerState { lives: START_LIVES, score: 0, wins: vec![false; 5], level }
+    }
+
+    /// Frog of the given player at its starting position, with the lives, score and home slots of the player.
+    /// When playing at the same time, each player has its own controls, and the frog stays on the board
+    /// after its last life to keep its home slots.
+    fn frog(&self, player: usize, config: &GameConfig) -> Frog
+    {
+        let simultaneous = config.simultaneous();
+        let mut frog = if simultaneous { Frog::with_player(START_POS_SIMULTANEOUS[player], player, player) }
+                       else { Frog::with_player(START_POS, player, 0) };
+
+        frog.lives = self.lives;
+        frog.score = self.score;
+        frog.wins = self.wins.clone();
+        frog.stays = simultaneous;
+        frog
+    }
+}
+
+#[derive(Clone)]
 pub struct FroggerGame 
 {
     arena: Arena,
+    config: GameConfig,
+    rng: Rng,
+    phase: Phase,
+    phase_ticks: i32,
+    players: Vec<PlayerState>,
+    turn: usize,
+    level: i32,
+    ticks: i32,
     playtime: i32,
+    score: i32,
+    stats: GameStats,
+    sounds: Vec<Sound>
 }
 impl FroggerGame 
 {
     pub fn new(size: Pt, n_vehicle_per_row: i32, n_raft_per_row: i32) -> FroggerGame 
     {
-        let mut arena = Arena::new(size);
+        FroggerGame::with_config(GameConfig { size, seed: random_seed(), n_vehicle_per_row, n_raft_per_row, ..GameConfig::default() })
+    }
+
+    /// Create a game whose evolution only depends on the configuration and on the keys pressed.
+    pub fn with_config(mut config: GameConfig) -> FroggerGame 
+    {
+        config.players = if config.simultaneous() { min(config.players, START_POS_SIMULTANEOUS.len()) } else { max(config.players, 1) };
+
+        let mut rng = Rng::new(config.seed);
+        let level = max(config.level, 1);
+        let players = vec![PlayerState::new(level); config.players];
+        let arena = FroggerGame::build_arena(&config, level, &mut rng, FroggerGame::board_frogs(&config, &players, 0));
+
+        FroggerGame{arena, 
+                    config,
+                    rng,
+                    phase: Phase::Ready,
+                    phase_ticks: 0,
+                    players,
+                    turn: 0,
+                    level,
+                    ticks: 0,
+                    playtime: 0,
+                    score: 0,
+                    stats: GameStats::default(),
+                    sounds: vec![]}
+    }
+
+    /// Populate a new arena for the given level: every level lanes move one pixel per tick faster.
+    fn build_arena(config: &GameConfig, level: i32, rng: &mut Rng, frogs: Vec<Frog>) -> Arena
+    {
+        let speed = |base: i32| base + base.signum() * (level - 1);
+        let mut arena = Arena::new(config.size, Rng::new(rng.randint(1, i32::MAX - 1) as u32));
 
         arena.spawn(Box::new(Water::new(pt(0, 82))));
 
-        for i in 0..n_vehicle_per_row
+        for i in 0..config.n_vehicle_per_row
         {
-            arena.spawn(Box::new(Vehicle::new(pt(i * 150 + randint(10, 50), 276), 4)));  // First row
-            arena.spawn(Box::new(Vehicle::new(pt(i * 150 + randint(10, 50), 308), -4)));  // Second row
-            arena.spawn(Box::new(Vehicle::new(pt(i * 150 + randint(10, 50), 340), 4)));  // Third row
-            arena.spawn(Box::new(Vehicle::new(pt(i * 150 + randint(10, 50), 372), -4)));  // Fourth row
-            arena.spawn(Box::new(Vehicle::new(pt(i * 150 + randint(10, 50), 404), 4)));  // Fifth row
+            arena.spawn(Box::new(Vehicle::new(pt(i * 150 + rng.randint(10, 50), 276), speed(4), rng)));  // First row
+            arena.spawn(Box::new(Vehicle::new(pt(i * 150 + rng.randint(10, 50), 308), speed(-4), rng)));  // Second row
+            arena.spawn(Box::new(Vehicle::new(pt(i * 150 + rng.randint(10, 50), 340), speed(4), rng)));  // Third row
+            arena.spawn(Box::new(Vehicle::new(pt(i * 150 + rng.randint(10, 50), 372), speed(-4), rng)));  // Fourth row
+            arena.spawn(Box::new(Vehicle::new(pt(i * 150 + rng.randint(10, 50), 404), speed(4), rng)));  // Fifth row
         }
 
-        for i in 0..n_raft_per_row
+        for i in 0..config.n_raft_per_row
         {
             // First row
-            let first_row_raft = Raft::new(pt(i * 400 + randint(0, 150), 87), 4);
+            let first_row_raft = Raft::new(pt(i * 400 + rng.randint(0, 150), 87), speed(4));
             let crocodile_offset = pt(first_row_raft.size.x + 100, 10); // compute the crocodile offsets w.r.t. the relative prior raft.
-            let first_row_crocodile = Crocodile::new(first_row_raft.pos.sub(crocodile_offset), 4);
+            let first_row_crocodile = Crocodile::new(first_row_raft.pos.sub(crocodile_offset), speed(4));
 
             // Second row
-            let second_row_turtle_first = Turtle::new(pt(i * 400 + randint(0, 150), 119), -3);
-            let second_row_turtle_second = Turtle::new(second_row_turtle_first.pos.add(pt(second_row_turtle_first.size.x + 8, 0)), -3);
-            let second_row_turtle_third = Turtle::new(second_row_turtle_second.pos.add(pt(second_row_turtle_second.size.x + 8, 0)), -3);
+            let second_row_turtle_first = Turtle::new(pt(i * 400 + rng.randint(0, 150), 119), speed(-3));
+            let second_row_turtle_second = Turtle::new(second_row_turtle_first.pos.add(pt(second_row_turtle_first.size.x + 8, 0)), speed(-3));
+            let second_row_turtle_third = Turtle::new(second_row_turtle_second.pos.add(pt(second_row_turtle_second.size.x + 8, 0)), speed(-3));
             
             // Third row
-            let third_row_raft = Raft::new(pt(i * 300 + randint(0, 150), 151), 3);
+            let third_row_raft = Raft::new(pt(i * 300 + rng.randint(0, 150), 151), speed(3));
 
             // Fourth row
-            let fourth_row_turtle_first = Turtle::new(pt(i * 300 + randint(0, 150), 183), -2);
-            let fourth_row_turtle_second = Turtle::new(fourth_row_turtle_first.pos.add(pt(fourth_row_turtle_first.size.x + 8, 0)), -2);
-            let fourth_row_turtle_third = Turtle::new(fourth_row_turtle_second.pos.add(pt(fourth_row_turtle_second.size.x + 8, 0)), -2); 
+            let fourth_row_turtle_first = Turtle::new(pt(i * 300 + rng.randint(0, 150), 183), speed(-2));
+            let fourth_row_turtle_second = Turtle::new(fourth_row_turtle_first.pos.add(pt(fourth_row_turtle_first.size.x + 8, 0)), speed(-2));
+            let fourth_row_turtle_third = Turtle::new(fourth_row_turtle_second.pos.add(pt(fourth_row_turtle_second.size.x + 8, 0)), speed(-2)); 
 
             // Fifth row
-            let fifth_row_raft = Raft::new(pt(i * 500 + randint(0, 150), 215), 2);
+            let fifth_row_raft = Raft::new(pt(i * 500 + rng.randint(0, 150), 215), speed(2));
 
             // First row
             arena.spawn(Box::new(first_row_raft));
@@ -531,51 +985,367 @@ impl FroggerGame
             arena.spawn(Box::new(fifth_row_raft));  
         }
 
-        arena.spawn(Box::new(Frog::new(pt(308, 440))));
+        for frog in frogs { arena.spawn(Box::new(frog)); }
+
+        arena
+    }
+
+    /// Frogs of the players on the board: all the ones still having lives when playing at the same
+    /// time, otherwise the one of the player in turn.
+    fn board_frogs(config: &GameConfig, players: &[PlayerState], turn: usize) -> Vec<Frog>
+    {
+        if config.simultaneous() 
+        { 
+            players.iter().enumerate().filter(|(_, state)| state.lives > 0).map(|(p, state)| state.frog(p, config)).collect() 
+        }
+        else { vec![players[turn].frog(turn, config)] }
+    }
 
-        FroggerGame{arena: arena, playtime: 0}
+    /// Indexes of the players whose frogs are on the board.
+    fn board_players(&self) -> Vec<usize>
+    {
+        if self.simultaneous() { (0..self.players.len()).collect() } else { vec![self.turn] }
     }
 
-    pub fn game_over(&self) -> bool { self.remaining_lives() <= 0 }
+    /// Frogs on the board: one, or one for each player playing at the same time.
+    pub fn frogs(&self) -> Vec<&Frog>
+    {
+        self.actors().iter().filter_map(|actor| actor.as_any().downcast_ref::<Frog>()).collect()
+    }
 
-    pub fn game_won(&self) -> bool
+    fn frog_of(&self, player: usize) -> Option<&Frog>
     {
-        let mut game_won: bool = false;
+        self.actors().iter().filter_map(|actor| actor.as_any().downcast_ref::<Frog>()).find(|hero| hero.player == player)
+    }
+
+    fn frog(&self) -> Option<&Frog> { self.frog_of(self.turn) }
+
+    pub fn phase(&self) -> Phase { self.phase }
+
+    /// Number of ticks spent in the current phase.
+    pub fn phase_ticks(&self) -> i32 { self.phase_ticks }
+
+    pub fn level(&self) -> i32 { self.level }
+
+    /// Index of the player whose frog is on the board; always the first one when playing at the same time.
+    pub fn turn(&self) -> usize { self.turn }
+
+    /// State of all the players; taking turns, the one of the current player is updated on turn changes only.
+    pub fn players(&self) -> &Vec<PlayerState> { &self.players }
+    pub fn config(&self) -> &GameConfig { &self.config }
+    pub fn simultaneous(&self) -> bool { self.config.simultaneous() }
+    pub fn game_over(&self) -> bool { self.phase == Phase::GameOver }
+    pub fn game_won(&self) -> bool { self.phase == Phase::Victory }
 
-        for actor in self.actors()
+    pub fn playing_time(&self) -> i32
+    {
+        self.playtime + self.arena.count() / 30
+    }
+
+    pub fn remaining_lives(&self) -> i32 { self.player_lives(self.turn) }
+
+    pub fn player_lives(&self, player: usize) -> i32
+    {
+        if player == self.turn && !self.simultaneous() { self.frog().map_or(0, |hero| hero.lives) }
+        else { self.players.get(player).map_or(0, |state| state.lives) }
+    }
+
+    pub fn player_score(&self, player: usize) -> i32
+    {
+        if player == self.turn { self.score }
+        else { self.players.get(player).map_or(0, |state| state.score) }
+    }
+
+    /// Player with the highest score in multiplayer games, unless tied.
+    pub fn winner(&self) -> Option<usize>
+    {
+        let scores: Vec<i32> = (0..self.players.len()).map(|p| self.player_score(p)).collect();
+        let best = *scores.iter().max()?;
+
+        if scores.len() > 1 && scores.iter().filter(|s| **s == best).count() == 1 { scores.iter().position(|s| *s == best) }
+        else { None }
+    }
+
+    /// Player who took each home slot, from left to right.
+    pub fn homes(&self) -> Vec<Option<usize>>
+    {
+        let frogs = self.frogs();
+        (0..HOME_SLOTS_X.len()).map(|i| frogs.iter().find(|hero| hero.wins[i]).map(|hero| hero.player)).collect()
+    }
+
+    pub fn remaining_time(&self) -> i32
+    {
+        match self.frog()
         {
-            if let Some(hero) = actor.as_any().downcast_ref::<Frog>()
+            Some(hero) => hero.time_left / 30,
+            None => 0
+        }
+    }
+
+    /// Time left to the frog to reach a home slot, as a fraction of the whole life time.
+    pub fn remaining_time_ratio(&self) -> f64 { self.player_time_ratio(self.turn) }
+
+    pub fn player_time_ratio(&self, player: usize) -> f64
+    {
+        match self.frog_of(player)
+        {
+            Some(hero) if !hero.out() => hero.time_left as f64 / LIFE_TICKS as f64,
+            _ => 0.0
+        }
+    }
+
+    /// Centre of the frog, or of the arena if there is no frog.
+    pub fn frog_centre(&self) -> Pt
+    {
+        match self.frog()
+        {
+            Some(hero) => hero.pos + hero.size / pt(2, 2),
+            None => self.size() / pt(2, 2)
+        }
+    }
+
+    /// Whether the frog is playing its death sequence.
+    pub fn dying(&self) -> bool
+    {
+        match self.frog()
+        {
+            Some(hero) => hero.dying(),
+            None => false
+        }
+    }
+
+    pub fn score(&self) -> i32 { self.score }
+    pub fn stats(&self) -> &GameStats { &self.stats }
+
+    /// Sounds of the events happened during the last tick.
+    pub fn sounds(&self) -> &[Sound] { &self.sounds }
+
+    /// Number of ticks since the beginning of the game, in every phase.
+    pub fn ticks(&self) -> i32 { self.ticks }
+
+    fn set_phase(&mut self, phase: Phase)
+    {
+        self.phase = phase;
+        self.phase_ticks = 0;
+    }
+
+    /// Copy lives, score and home slots of the frogs on the board into the state of their players.
+    fn save_players(&mut self)
+    {
+        for p in self.board_players()
+        {
+            let saved = self.frog_of(p).map(|hero| (hero.lives, hero.score, hero.wins.clone()));
+            let score = if p == self.turn && !self.simultaneous() { self.score } else { self.players[p].score };
+            let player = &mut self.players[p];
+
+            match saved
             {
-                game_won = if hero.wins.contains(&false) { false } else { true };
-                break;
+                Some((lives, score, wins)) => 
+                {
+                    player.lives = lives;
+                    player.score = score;
+                    player.wins = wins;
+                }
+                None => 
+                {
+                    player.lives = 0;
+                    player.score = score;
+                }
             }
+            player.level = self.level;
         }
+    }
+
+    /// Replace the arena with a new one for the players on the board, at the level of the current one.
+    fn load_players(&mut self)
+    {
+        let player = &self.players[self.turn];
 
-        game_won
+        self.playtime += self.arena.count() / 30;
+        self.level = player.level;
+        self.score = player.score;
+        self.arena = FroggerGame::build_arena(&self.config, self.level, &mut self.rng, FroggerGame::board_frogs(&self.config, &self.players, self.turn));
     }
 
-    pub fn playing_time(&self) -> i32
+    /// Give the turn to the next player still having lives, if any other.
+    fn switch_turn(&mut self) -> bool
     {
-        self.playtime + self.arena.count() / 30
+        let n = self.players.len();
+        let next = (1..n).map(|i| (self.turn + i) % n).find(|p| self.players[*p].lives > 0);
+
+        self.save_players();
+
+        match next
+        {
+            Some(player) =>
+            {
+                self.turn = player;
+                self.load_players();
+                self.set_phase(Phase::Ready);
+                true
+            }
+            None => false
+        }
+    }
+
+    /// Save the players and award the 1000 points bonus of the cleared level: to the player in turn, 
+    /// to all the players in co-op, to the ones who took the most home slots in versus.
+    fn clear_level(&mut self)
+    {
+        self.save_players();
+
+        let homes = self.homes();
+        let taken = |player: usize| homes.iter().filter(|h| **h == Some(player)).count();
+        let most = (0..self.players.len()).map(taken).max().unwrap_or(0);
+
+        for p in self.board_players()
+        {
+            let bonus = match self.config.mode
+            {
+                Mode::Versus if self.simultaneous() => taken(p) == most,
+                _ => true
+            };
+
+            if bonus 
+            { 
+                let player = &mut self.players[p];
+
+                if extra_life(player.score, 1000) 
+                { 
+                    player.lives += 1;
+                    self.sounds.push(Sound::ExtraLife);
+                }
+                player.score += 1000; 
+            }
+        }
+
+        self.score = self.players[self.turn].score;
+        self.set_phase(Phase::LevelClear);
+    }
+
+    /// Replace the arena with the one of the next level, keeping lives and score of the players.
+    fn next_level(&mut self)
+    {
+        for p in self.board_players()
+        {
+            let player = &mut self.players[p];
+            player.level += 1;
+            player.wins = vec![false; 5];
+        }
+
+        self.load_players();
     }
 
-    pub fn remaining_lives(&self) -> i32
+    /// Sounds of what happened to the frogs on the board during the tick, given the frogs before it.
+    fn frog_sounds(&self, before: &[Frog]) -> Vec<Sound>
     {
-        let mut lives: i32 = 0;
+        let mut sounds: Vec<Sound> = vec![];
+
+        for hero in self.frogs()
+        {
+            if hero.hopped { sounds.push(Sound::Hop); }
+            if let Some(cause) = hero.died { sounds.push(Sound::of_death(cause)); }
+            if hero.extra_life { sounds.push(Sound::ExtraLife); }
+
+            if let Some(old) = before.iter().find(|old| old.player == hero.player)
+            {
+                let homes = |frog: &Frog| frog.wins.iter().filter(|w| **w).count();
+
+                if homes(hero) > homes(old) { sounds.push(Sound::Home); }
+                if !hero.dying() && old.time_left >= TIME_WARNING_TICKS && hero.time_left < TIME_WARNING_TICKS { sounds.push(Sound::TimeWarning); }
+            }
+        }
+
+        sounds
+    }
+
+    pub fn tick(&mut self, input: &InputState) 
+    { 
+        let phase = self.phase;
+        let before: Vec<Frog> = self.frogs().into_iter().cloned().collect();
 
-        for actor in self.actors()
+        self.ticks += 1;
+        self.phase_ticks += 1;
+        self.sounds.clear();
+
+        match self.phase
         {
-            if let Some(hero) = actor.as_any().downcast_ref::<Frog>() 
+            Phase::Ready =>
+            {
+                if self.phase_ticks >= READY_TICKS { self.set_phase(Phase::Playing); }
+            }
+            Phase::Playing if self.simultaneous() =>
+            {
+                // Each frog plays its death sequences while the other ones keep playing
+                self.arena.tick(input);
+                self.sounds = self.frog_sounds(&before);
+
+                let tick = self.ticks;
+                let deaths: Vec<Death> = self.frogs().iter()
+                    .filter_map(|hero| hero.died().map(|cause| Death { cause, pos: hero.pos, tick, player: hero.player }))
+                    .collect();
+
+                self.stats.deaths.extend(deaths);
+                self.save_players();
+                self.score = self.players[self.turn].score;
+
+                if self.frogs().iter().all(|hero| hero.out()) { self.set_phase(Phase::GameOver); }
+                else if !self.homes().contains(&None) { self.clear_level(); }
+            }
+            Phase::Playing | Phase::Dying =>
+            {
+                // Keys are ignored while the death sequence plays
+                let idle = InputState::default();
+                self.arena.tick(if self.phase == Phase::Playing { input } else { &idle });
+                self.sounds = self.frog_sounds(&before);
+
+                let tick = self.ticks;
+
+                if let Some((score, died, pos)) = self.frog().map(|hero| (hero.score, hero.died(), hero.pos))
+                {
+                    self.score = score;
+
+                    if let Some(cause) = died
+                    {
+                        self.stats.deaths.push(Death { cause, pos, tick, player: self.turn });
+                    }
+                }
+
+                match self.frog()
+                {
+                    None => { if !self.switch_turn() { self.set_phase(Phase::GameOver); } }
+                    Some(hero) if hero.dying() => { if self.phase != Phase::Dying { self.set_phase(Phase::Dying); } }
+                    Some(hero) if !hero.wins.contains(&false) => self.clear_level(),
+                    Some(_) if self.phase == Phase::Dying => 
+                    { 
+                        // Death sequence over: in turns, the other player plays next
+                        if !self.switch_turn() { self.set_phase(Phase::Playing); }
+                    }
+                    Some(_) => { }
+                }
+            }
+            Phase::LevelClear =>
             {
-                lives = hero.lives;
-                break;
+                if self.phase_ticks >= LEVEL_CLEAR_TICKS
+                {
+                    if self.level >= max(LEVELS, self.config.level)
+                    { 
+                        self.set_phase(Phase::Victory);
+                    }
+                    else
+                    {
+                        self.next_level();
+                        self.set_phase(Phase::Ready);
+                    }
+                }
             }
+            Phase::GameOver | Phase::Victory => { }
         }
 
-        lives
+        if self.phase == Phase::LevelClear && phase != Phase::LevelClear { self.sounds.push(Sound::LevelClear); }
     }
 
-    pub fn tick(&mut self, keys: String) { self.arena.tick(keys); }
     pub fn size(&self) -> Pt { self.arena.size() }
+    pub fn arena(&self) -> &Arena { &self.arena }
     pub fn actors(&self) -> &Vec<Box<dyn Actor>> { self.arena.actors() }
 }
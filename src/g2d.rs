@@ -11,14 +11,25 @@ extern
     pub fn update_canvas();
     pub fn current_keys() -> String;
     pub fn previous_keys() -> String;
+    pub fn pressed_keys() -> String;
     pub fn mouse_clicked() -> bool;
     pub fn load_image(src: String) -> String;
     pub fn main_loop(fps: i32);
     pub fn close_canvas();
+    pub fn load_data(key: String) -> String;
+    pub fn save_data(key: String, value: String);
+    pub fn current_date() -> String;
+    pub fn post_data(url: String, body: String);
+    pub fn load_sound(name: String, src: String);
+    pub fn play_sound(name: String, volume: f64);
+    pub fn play_music(name: String, src: String, volume: f64, fade: f64);
+    pub fn set_music_volume(volume: f64);
 
     fn js_init_canvas(w: i32, h: i32);
     fn js_fill_circle(x: i32, y: i32, r: i32);
     fn js_fill_rect(x: i32, y: i32, w: i32, h: i32);
+    fn js_draw_rect(x: i32, y: i32, w: i32, h: i32);
+    fn js_draw_line(x1: i32, y1: i32, x2: i32, y2: i32);
     fn js_draw_text(txt: String, x: i32, y: i32, size: i32, baseline: String, align: String);
     fn js_draw_image(src: String, x: i32, y: i32, xc: i32, yc: i32, wc: i32, hc: i32);
     fn js_mouse_x() -> i32;
@@ -40,6 +51,16 @@ pub fn fill_rect(pos: Pt, size: Pt)
     js_fill_rect(pos.x, pos.y, size.x, size.y);
 }
 
+pub fn draw_rect(pos: Pt, size: Pt) 
+{
+    js_draw_rect(pos.x, pos.y, size.x, size.y);
+}
+
+pub fn draw_line(pt1: Pt, pt2: Pt) 
+{
+    js_draw_line(pt1.x, pt1.y, pt2.x, pt2.y);
+}
+
 pub fn draw_image(src: String, pos: Pt) 
 {
     js_draw_image(src, pos.x, pos.y, 0, 0, 0, 0);
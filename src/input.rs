@@ -0,0 +1,435 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::pt2d::*;
+
+/// Most players sharing the keyboard, one for each frog on the board.
+pub const MAX_PLAYERS: usize = 2;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Key
+{
+    Up,
+    Down,
+    Left,
+    Right,
+    Enter,
+    Space,
+    Escape,
+    Backspace,
+    Function(u8),  // F1 to F12
+    Char(char),    // Printable keys, lower case
+    Mouse(u8)      // Left, middle, right and other buttons
+}
+impl Key
+{
+    /// Parse the name of a key, as found in the comma separated lists sent by g2d.
+    pub fn from_name(name: &str) -> Option<Key>
+    {
+        match name
+        {
+            "ArrowUp" => Some(Key::Up),
+            "ArrowDown" => Some(Key::Down),
+            "ArrowLeft" => Some(Key::Left),
+            "ArrowRight" => Some(Key::Right),
+            "Enter" => Some(Key::Enter),
+            "Spacebar" => Some(Key::Space),
+            "Escape" => Some(Key::Escape),
+            "Backspace" => Some(Key::Backspace),
+            "LeftButton" => Some(Key::Mouse(0)),
+            "MiddleButton" => Some(Key::Mouse(1)),
+            "RightButton" => Some(Key::Mouse(2)),
+            "MouseButton" => Some(Key::Mouse(3)),
+            _ if name.chars().count() == 1 => name.chars().next().map(|c| Key::Char(c.to_ascii_lowercase())),
+            _ if name.starts_with('F') => name[1..].parse::<u8>().ok().filter(|n| (1..=12).contains(n)).map(Key::Function),
+            _ => None
+        }
+    }
+
+    /// Name of the key in the g2d protocol.
+    pub fn name(&self) -> String
+    {
+        match self
+        {
+            Key::Up => "ArrowUp".to_string(),
+            Key::Down => "ArrowDown".to_string(),
+            Key::Left => "ArrowLeft".to_string(),
+            Key::Right => "ArrowRight".to_string(),
+            Key::Enter => "Enter".to_string(),
+            Key::Space => "Spacebar".to_string(),
+            Key::Escape => "Escape".to_string(),
+            Key::Backspace => "Backspace".to_string(),
+            Key::Function(n) => format!("F{}", n),
+            Key::Char(c) => c.to_string(),
+            Key::Mouse(0) => "LeftButton".to_string(),
+            Key::Mouse(1) => "MiddleButton".to_string(),
+            Key::Mouse(2) => "RightButton".to_string(),
+            Key::Mouse(_) => "MouseButton".to_string()
+        }
+    }
+}
+
+/// Parse a comma separated list of key names, skipping the unknown ones.
+pub fn parse_keys(keys: &str) -> HashSet<Key>
+{
+    keys.split(',').filter_map(Key::from_name).collect()
+}
+
+/// Parse a comma separated list of key names keeping their order, skipping the unknown ones.
+pub fn parse_key_list(keys: &str) -> Vec<Key>
+{
+    keys.split(',').filter_map(Key::from_name).collect()
+}
+
+/// Format a set of keys as a comma separated list of key names.
+pub fn format_keys(keys: &HashSet<Key>) -> String
+{
+    let mut names: Vec<String> = keys.iter().map(|k| k.name()).collect();
+    names.sort();
+    names.join(",")
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Action
+{
+    Up,
+    Down,
+    Left,
+    Right,
+    Pause,
+    Restart
+}
+impl Action
+{
+    pub const ALL: [Action; 6] = [Action::Up, Action::Down, Action::Left, Action::Right, Action::Pause, Action::Restart];
+
+    pub fn from_name(name: &str) -> Option<Action>
+    {
+        Action::ALL.iter().copied().find(|a| format!("{:?}", a).eq_ignore_ascii_case(name))
+    }
+
+    /// Hop direction of the movement actions.
+    pub fn direction(&self) -> Option<Pt>
+    {
+        match self
+        {
+            Action::Up => Some(pt(0, -1)),
+            Action::Down => Some(pt(0, 1)),
+            Action::Left => Some(pt(-1, 0)),
+            Action::Right => Some(pt(1, 0)),
+            _ => None
+        }
+    }
+}
+
+/// Keys triggering each action.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyBindings
+{
+    bindings: HashMap<Action, Vec<Key>>
+}
+impl Default for KeyBindings
+{
+    fn default() -> Self { KeyBindings::arrows() }
+}
+impl KeyBindings
+{
+    fn with_moves(up: Key, down: Key, left: Key, right: Key) -> KeyBindings
+    {
+        let mut bindings = HashMap::new();
+
+        bindings.insert(Action::Up, vec![up]);
+        bindings.insert(Action::Down, vec![down]);
+        bindings.insert(Action::Left, vec![left]);
+        bindings.insert(Action::Right, vec![right]);
+        bindings.insert(Action::Pause, vec![Key::Char('p'), Key::Escape]);
+        bindings.insert(Action::Restart, vec![Key::Enter, Key::Space]);
+
+        KeyBindings { bindings }
+    }
+
+    pub fn arrows() -> KeyBindings { KeyBindings::with_moves(Key::Up, Key::Down, Key::Left, Key::Right) }
+    pub fn wasd() -> KeyBindings { KeyBindings::with_moves(Key::Char('w'), Key::Char('s'), Key::Char('a'), Key::Char('d')) }
+    pub fn vim() -> KeyBindings { KeyBindings::with_moves(Key::Char('k'), Key::Char('j'), Key::Char('h'), Key::Char('l')) }
+
+    /// Bindings by preset name: arrows, wasd or vim.
+    pub fn preset(name: &str) -> Option<KeyBindings>
+    {
+        match name.trim().to_ascii_lowercase().as_str()
+        {
+            "arrows" => Some(KeyBindings::arrows()),
+            "wasd" => Some(KeyBindings::wasd()),
+            "vim" => Some(KeyBindings::vim()),
+            _ => None
+        }
+    }
+
+    /// Parse either a preset name, or lines like `Up = ArrowUp, w` overriding the default bindings.
+    /// Empty lines and lines starting with `#` are skipped.
+    pub fn from_config(text: &str) -> Result<KeyBindings, String>
+    {
+        if let Some(bindings) = KeyBindings::preset(text) { return Ok(bindings); }
+
+        let mut bindings = KeyBindings::default();
+
+        for line in text.lines().map(|l| l.trim()).filter(|l| !l.is_empty() && !l.starts_with('#'))
+        {
+            let (name, keys) = line.split_once('=').ok_or(format!("Missing '=' in: {}", line))?;
+            let action = Action::from_name(name.trim()).ok_or(format!("Unknown action: {}", name.trim()))?;
+            let mut bound: Vec<Key> = vec![];
+
+            for key in keys.split(',').map(|k| k.trim()).filter(|k| !k.is_empty())
+            {
+                bound.push(Key::from_name(key).ok_or(format!("Unknown key: {}", key))?);
+            }
+
+            bindings.bindings.insert(action, bound);
+        }
+
+        Ok(bindings)
+    }
+
+    /// Read the bindings from a configuration file, in the syntax accepted by `from_config`.
+    pub fn load(path: &str) -> Result<KeyBindings, String>
+    {
+        let text = std::fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        KeyBindings::from_config(&text)
+    }
+
+    /// Format the bindings in the syntax accepted by `from_config`.
+    pub fn to_config(&self) -> String
+    {
+        Action::ALL.iter()
+            .map(|a| format!("{:?} = {}", a, self.keys(*a).iter().map(|k| k.name()).collect::<Vec<String>>().join(", ")))
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    pub fn keys(&self, action: Action) -> &[Key]
+    {
+        self.bindings.get(&action).map(|keys| keys.as_slice()).unwrap_or(&[])
+    }
+
+    pub fn bind(&mut self, action: Action, key: Key)
+    {
+        let keys = self.bindings.entry(action).or_default();
+        if !keys.contains(&key) { keys.push(key); }
+    }
+
+    pub fn unbind(&mut self, action: Action, key: Key)
+    {
+        if let Some(keys) = self.bindings.get_mut(&action) { keys.retain(|k| *k != key); }
+    }
+}
+
+/// Keys held down in the current and in the previous tick, and the actions they are bound to.
+/// Keys pressed since the previous tick are also kept in order, so that keys pressed and released
+/// between two ticks are not lost.
+#[derive(Clone, Debug, PartialEq)]
+pub struct InputState
+{
+    current: HashSet<Key>,
+    previous: HashSet<Key>,
+    presses: Vec<Key>,
+    triggered: HashSet<Action>,
+    bindings: KeyBindings,
+    others: Vec<KeyBindings>  // Bindings of the second player onwards, sharing the keyboard
+}
+impl Default for InputState
+{
+    fn default() -> Self
+    {
+        InputState { current: HashSet::new(),
+                     previous: HashSet::new(),
+                     presses: vec![],
+                     triggered: HashSet::new(),
+                     bindings: KeyBindings::default(),
+                     others: vec![KeyBindings::wasd()] }
+    }
+}
+impl InputState
+{
+    pub fn new() -> InputState { InputState::default() }
+
+    /// Input with the given keys held down in the current tick, and no keys in the previous one.
+    pub fn with_keys(held: HashSet<Key>) -> InputState
+    {
+        let mut input = InputState::default();
+        input.update(held);
+        input
+    }
+
+    pub fn bindings(&self) -> &KeyBindings { &self.bindings }
+    pub fn set_bindings(&mut self, bindings: KeyBindings) { self.bindings = bindings; }
+
+    /// Bindings of the given player, when more players share the keyboard: the first one uses the
+    /// main bindings, the second one WASD unless changed. Other players have no keys.
+    pub fn player_bindings(&self, player: usize) -> Option<&KeyBindings>
+    {
+        if player == 0 { Some(&self.bindings) } else { self.others.get(player - 1) }
+    }
+
+    /// Fails for players past `MAX_PLAYERS`, who have no frog to move.
+    pub fn set_player_bindings(&mut self, player: usize, bindings: KeyBindings) -> Result<(), String>
+    {
+        if player >= MAX_PLAYERS { return Err(format!("Bad player: {}", player)); }
+        if player == 0 { self.bindings = bindings; return Ok(()); }
+
+        while self.others.len() < player { self.others.push(KeyBindings { bindings: HashMap::new() }); }
+        self.others[player - 1] = bindings;
+        Ok(())
+    }
+
+    /// Move to the next tick, with the given keys held down.
+    pub fn update(&mut self, held: HashSet<Key>)
+    {
+        self.update_with_presses(held, vec![]);
+    }
+
+    /// Move to the next tick, with the given keys held down and the given keys pressed since the
+    /// previous tick, in order. Held keys missing from `presses` are considered pressed after them.
+    pub fn update_with_presses(&mut self, held: HashSet<Key>, presses: Vec<Key>)
+    {
+        self.previous = std::mem::replace(&mut self.current, held);
+        self.presses = presses;
+        self.triggered.clear();
+
+        let mut new_keys: Vec<Key> = self.current.iter().copied()
+            .filter(|k| !self.previous.contains(k) && !self.presses.contains(k))
+            .collect();
+        // Same order of the bound actions, not to depend on the order of the set
+        new_keys.sort_by_key(|k| (Action::ALL.iter().position(|a| self.bindings.keys(*a).contains(k)).unwrap_or(Action::ALL.len()), k.name()));
+        self.presses.extend(new_keys);
+    }
+
+    /// Fire an action for the current tick only, without any key (e.g. from a touch gesture).
+    pub fn trigger(&mut self, action: Action) { self.triggered.insert(action); }
+
+    pub fn pressed(&self, key: Key) -> bool { self.current.contains(&key) }
+    pub fn just_pressed(&self, key: Key) -> bool { self.presses.contains(&key) }
+    pub fn just_released(&self, key: Key) -> bool { !self.current.contains(&key) && self.previous.contains(&key) }
+    pub fn any_just_pressed(&self) -> bool { !self.presses.is_empty() }
+    pub fn presses(&self) -> &Vec<Key> { &self.presses }
+    pub fn held(&self) -> &HashSet<Key> { &self.current }
+
+    pub fn action_pressed(&self, action: Action) -> bool 
+    { 
+        self.triggered.contains(&action) || self.bindings.keys(action).iter().any(|k| self.pressed(*k))
+    }
+
+    pub fn action_just_pressed(&self, action: Action) -> bool 
+    { 
+        self.triggered.contains(&action) || self.bindings.keys(action).iter().any(|k| self.just_pressed(*k))
+    }
+
+    /// Actions pressed since the previous tick, each listed once, in the order they were pressed.
+    /// Actions whose order is unknown (triggered by gestures, or keys pressed at the same time
+    /// without an order) follow the order of `Action::ALL`: vertical moves before horizontal ones.
+    pub fn actions_just_pressed(&self) -> Vec<Action>
+    {
+        let mut actions = InputState::bound_actions(&self.presses, &self.bindings);
+        let mut unordered: Vec<Action> = self.triggered.iter().copied().collect();
+        unordered.sort_by_key(|a| Action::ALL.iter().position(|b| b == a));
+
+        for action in unordered
+        {
+            if !actions.contains(&action) { actions.push(action); }
+        }
+
+        actions
+    }
+
+    /// Actions pressed since the previous tick by the given player, with its own bindings.
+    /// Actions triggered without keys belong to the first player.
+    pub fn player_actions_just_pressed(&self, player: usize) -> Vec<Action>
+    {
+        match self.player_bindings(player)
+        {
+            _ if player == 0 => self.actions_just_pressed(),
+            Some(bindings) => InputState::bound_actions(&self.presses, bindings),
+            None => vec![]
+        }
+    }
+
+    /// Actions bound to the given keys, each listed once, in the order of the keys.
+    fn bound_actions(keys: &[Key], bindings: &KeyBindings) -> Vec<Action>
+    {
+        let mut actions: Vec<Action> = vec![];
+
+        for key in keys
+        {
+            for action in Action::ALL.iter().filter(|a| bindings.keys(**a).contains(key))
+            {
+                if !actions.contains(action) { actions.push(*action); }
+            }
+        }
+
+        actions
+    }
+
+    pub fn action_just_released(&self, action: Action) -> bool 
+    { 
+        self.bindings.keys(action).iter().any(|k| self.just_released(*k))
+    }
+}
+
+/// Recognise taps and swipes of a mouse or of a finger, turning them into hop actions.
+#[derive(Clone, Debug)]
+pub struct Gestures
+{
+    start: Option<Pt>,
+    last: Pt,
+    threshold: i32
+}
+impl Default for Gestures
+{
+    fn default() -> Self { Gestures::new(24) }
+}
+impl Gestures
+{
+    /// Pointers moving less than `threshold` pixels between press and release are taps.
+    pub fn new(threshold: i32) -> Gestures
+    {
+        Gestures { start: None, last: pt(0, 0), threshold }
+    }
+
+    fn direction(delta: Pt) -> Action
+    {
+        if delta.x.abs() > delta.y.abs() 
+        {
+            if delta.x < 0 { Action::Left } else { Action::Right }
+        }
+        else 
+        {
+            if delta.y < 0 { Action::Up } else { Action::Down }
+        }
+    }
+
+    /// Feed the pointer state of the current tick. When the pointer is released, return the action
+    /// of the gesture: the direction of a swipe, or the side of `target` (e.g. the frog) tapped on.
+    pub fn update(&mut self, down: bool, pos: Pt, target: Pt) -> Option<Action>
+    {
+        match (self.start, down)
+        {
+            (None, true) => 
+            {
+                self.start = Some(pos);
+                self.last = pos;
+                None
+            }
+            (Some(_), true) => 
+            {
+                self.last = pos;
+                None
+            }
+            (Some(start), false) => 
+            {
+                self.start = None;
+                let swipe = self.last - start;
+
+                if swipe.x.abs() >= self.threshold || swipe.y.abs() >= self.threshold { Some(Gestures::direction(swipe)) }
+                else { Some(Gestures::direction(start - target)) }
+            }
+            (None, false) => None
+        }
+    }
+}
@@ -1,79 +1,696 @@
 use wasm_bindgen::prelude::*;
 use std::cell::RefCell;
 
+use audio::SoundSink;
+use scores::ScoreStore;
+
 pub mod actor;
+pub mod api;
+pub mod audio;
+pub mod bot;
+pub mod env;
 pub mod frogger;
 pub mod g2d;
+pub mod input;
+pub mod net;
+pub mod observe;
 pub mod pt2d;
 pub mod rand;
+pub mod replay;
+pub mod scores;
+pub mod settings;
+pub mod solver;
+pub mod stream;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Screen 
+{
+    Title,
+    Playing,
+    Paused,
+    EnterName(usize),  // A player typing its name for the high-score table
+    Scores,
+    Demo               // The bot playing on its own, after a while on the title screen
+}
+
+/// Key of the high-score table in the local storage of the browser.
+const HIGH_SCORES_KEY: &str = "frogger-high-scores";
+/// Key of the settings in the local storage of the browser.
+const SETTINGS_KEY: &str = "frogger-settings";
+/// Ticks the volume stays on screen after changing it.
+const VOLUME_NOTICE_TICKS: i32 = 45;
+/// Ticks without any key pressed on the title screen before the demo starts.
+const DEMO_IDLE_TICKS: i32 = 30 * 15;
+/// Longest demo, in ticks, if the bot does not lose all its lives before.
+const DEMO_TICKS: i32 = 30 * 60;
 
 pub struct FroggerGui 
 {
-    game: frogger::FroggerGame
+    game: frogger::FroggerGame,
+    screen: Screen,
+    input: input::InputState,
+    gestures: input::Gestures,
+    players: usize,
+    mode: frogger::Mode,
+    spectator: Option<stream::StreamDecoder>,
+    spectated: Option<stream::View>,
+    broadcast: Option<(String, stream::Broadcast)>,  // Relay the game played is published to, and its stream
+    leaderboard: Option<String>,  // Where results are submitted, if the page is served by the leaderboard server
+    high_scores: Option<scores::HighScores>,
+    recorded: bool,       // Results of the last game already checked for the high-score table
+    pending: Vec<usize>,  // Players still to enter their names
+    name: String,
+    replay: replay::Replay,
+    idle: i32,            // Ticks spent on the title screen without any key pressed
+    demo: Option<(bot::Bot, input::InputState)>,
+    audio: audio::WebAudio,
+    music: audio::Music,
+    settings: settings::Settings,
+    volume_notice: i32,   // Ticks left showing the volume
+    debug: bool
 }
 impl FroggerGui 
 {
     pub fn new() -> FroggerGui 
     {
         let game = frogger::FroggerGame::new(pt2d::pt(640, 480), 5, 2);
-        FroggerGui{game}
+        FroggerGui{game, 
+                   screen: Screen::Title, 
+                   input: input::InputState::new(), 
+                   gestures: input::Gestures::default(), 
+                   players: 1,
+                   mode: frogger::Mode::Turns,
+                   spectator: None,
+                   spectated: None,
+                   broadcast: None,
+                   leaderboard: None,
+                   high_scores: None,
+                   recorded: false,
+                   pending: vec![],
+                   name: String::new(),
+                   replay: replay::Replay::new(frogger::GameConfig::default()),
+                   idle: 0,
+                   demo: None,
+                   audio: audio::WebAudio::new(),
+                   music: audio::Music::new(),
+                   settings: settings::Settings::default(),
+                   volume_notice: 0,
+                   debug: false}
     }
 
-    pub fn setup(&self) 
+    pub fn setup(&mut self) 
     {
         g2d::init_canvas(self.game.size());
+        self.load_settings();
+        self.audio.load();
         g2d::main_loop(30);
     }
 
-    pub fn tick(&mut self) 
+    /// Apply the settings saved in the browser: controls and volumes.
+    fn load_settings(&mut self) 
     {
-        g2d::clear_canvas();
-        g2d::draw_image_clip("frogger-bg.png".to_string(), pt2d::pt(0, 0), pt2d::pt(0, 0), pt2d::pt(640, 480));
-        
+        self.settings = settings::Settings::load(SETTINGS_KEY);
+
+        for (player, config) in &self.settings.key_bindings 
+        {
+            if let Ok(bindings) = input::KeyBindings::from_config(config) { let _ = self.input.set_player_bindings(*player, bindings); }
+        }
+
+        self.audio.set_volume(self.settings.audio.effects_volume());
+    }
+
+    /// Change the audio settings, apply and save them.
+    pub fn change_audio(&mut self, change: impl FnOnce(&mut audio::AudioSettings) -> Result<(), String>) -> Result<(), String> 
+    {
+        change(&mut self.settings.audio)?;
+        self.audio.set_volume(self.settings.audio.effects_volume());
+        self.settings.save(SETTINGS_KEY);
+        self.volume_notice = VOLUME_NOTICE_TICKS;
+        Ok(())
+    }
+
+    /// Music of the current screen, or of the phase of the game being played.
+    fn track(&self) -> audio::Track 
+    {
+        match self.screen 
+        {
+            Screen::Title | Screen::Scores | Screen::Demo => audio::Track::Title,
+            Screen::Playing | Screen::Paused | Screen::EnterName(_) => audio::Track::of(self.game.phase())
+        }
+    }
+
+    /// Start a brand new game, without reloading the page.
+    pub fn restart(&mut self) 
+    {
+        let config = frogger::GameConfig { seed: rand::random_seed(), players: self.players, mode: self.mode, ..frogger::GameConfig::default() };
+        self.game = frogger::FroggerGame::with_config(config);
+        self.replay = replay::Replay::new(*self.game.config());
+        self.screen = Screen::Playing;
+        self.recorded = false;
+    }
+
+    /// Let the bot play a single player game, as a demo.
+    fn start_demo(&mut self) 
+    {
+        let config = frogger::GameConfig { seed: rand::random_seed(), players: 1, mode: frogger::Mode::Turns, ..frogger::GameConfig::default() };
+        self.game = frogger::FroggerGame::with_config(config);
+        self.demo = Some((bot::Bot::new(bot::BotConfig::easy(), 0), input::InputState::new()));
+        self.screen = Screen::Demo;
+    }
+
+    /// Back to the title screen, over a board at rest.
+    fn stop_demo(&mut self) 
+    {
+        self.game = frogger::FroggerGame::new(self.game.size(), 5, 2);
+        self.demo = None;
+        self.idle = 0;
+        self.screen = Screen::Title;
+    }
+
+    /// The high-score table, loaded from the local storage of the browser the first time.
+    fn high_scores(&mut self) -> &mut scores::HighScores 
+    {
+        self.high_scores.get_or_insert_with(|| scores::LocalStore::new(HIGH_SCORES_KEY).load().unwrap_or_default())
+    }
+
+    /// At the end of a game, ask the players good enough for the high-score table for their names.
+    fn record_results(&mut self) 
+    {
+        let results: Vec<(i32, i32)> = (0..self.players).map(|p| (self.game.player_score(p), self.game.playing_time())).collect();
+        let table = self.high_scores();
+
+        self.pending = (0..results.len()).filter(|p| table.rank(results[*p].0, results[*p].1).is_some()).collect();
+        self.recorded = true;
+
+        if !self.pending.is_empty() { self.next_name(); }
+    }
+
+    /// Ask the next player for its name, or show the table when all the names were entered.
+    fn next_name(&mut self) 
+    {
+        self.name.clear();
+        self.screen = if self.pending.is_empty() { Screen::Scores } else { Screen::EnterName(self.pending.remove(0)) };
+    }
+
+    /// Add the result of the player to the high-score table, and save it. The result is also sent
+    /// with the replay of the game to the leaderboard, if any.
+    fn save_result(&mut self, player: usize) 
+    {
+        let name = if self.name.is_empty() { format!("FROG {}", player + 1) } else { self.name.clone() };
+
+        if let Some(url) = &self.leaderboard 
+        {
+            let submission = format!("name {}\nplayer {}\nscore {}\n{}", name, player, self.game.player_score(player), self.replay.to_text());
+            g2d::post_data(url.clone(), submission);
+        }
+
+        let entry = scores::HighScore { name,
+                                        score: self.game.player_score(player),
+                                        level: self.game.players()[player].level,
+                                        time: self.game.playing_time(),
+                                        date: g2d::current_date() };
+        let table = self.high_scores();
+
+        table.insert(entry);
+        let _ = scores::LocalStore::new(HIGH_SCORES_KEY).save(table);
+    }
+
+    pub fn screen(&self) -> Screen { self.screen }
+
+    pub fn set_key_bindings(&mut self, bindings: input::KeyBindings) { self.input.set_bindings(bindings); }
+    pub fn set_player_key_bindings(&mut self, player: usize, bindings: input::KeyBindings) -> Result<(), String> { self.input.set_player_bindings(player, bindings) }
+
+    /// Keep the controls of a player in the settings saved in the browser, for the next visits.
+    pub fn save_key_bindings(&mut self, player: usize, config: &str) 
+    {
+        self.settings.set_key_bindings(player, config);
+        self.settings.save(SETTINGS_KEY);
+    }
+
+    /// Colour used by the debug overlay for each kind of actor.
+    fn debug_color(actor: &dyn actor::Actor) -> (i32, i32, i32)
+    {
+        match frogger::ActorKind::of(actor) 
+        {
+            frogger::ActorKind::Frog => (255, 255, 255),
+            frogger::ActorKind::Vehicle => (255, 0, 0),
+            frogger::ActorKind::Raft => (255, 160, 0),
+            frogger::ActorKind::Turtle => (0, 255, 0),
+            frogger::ActorKind::Crocodile => (255, 0, 255),
+            frogger::ActorKind::Water => (0, 160, 255),
+            frogger::ActorKind::Other => (127, 127, 127)
+        }
+    }
+
+    /// Draw hitboxes, the collision detection grid, the home slots and the colliding pairs.
+    fn draw_debug(&self) 
+    {
+        let arena = self.game.arena();
+        let tile = arena.tile_size();
+        let tiles = arena.tiles();
+
+        g2d::set_color(80, 80, 80);
+
+        for (i, count) in arena.occupancy().iter().enumerate() 
+        {
+            let pos = pt2d::pt(i as i32 % tiles.x, i as i32 / tiles.x) * tile;
+            g2d::draw_rect(pos, tile);
+            g2d::draw_text(format!("{}", count), pos + pt2d::pt(2, 2), 10);
+        }
+
+        g2d::set_color(255, 255, 0);
+
+        for (pos, size) in frogger::home_slots() 
+        {
+            g2d::draw_rect(pos, size);
+        }
+
         for b in self.game.actors() 
         {
-            if let Some(img) = b.sprite() 
+            let (red, green, blue) = FroggerGui::debug_color(&**b);
+            g2d::set_color(red, green, blue);
+            g2d::draw_rect(b.pos(), b.size());
+        }
+
+        let centres: Vec<pt2d::Pt> = self.game.actors().iter().map(|b| b.pos() + b.size() / pt2d::pt(2, 2)).collect();
+        g2d::set_color(255, 255, 255);
+
+        for (i, j) in arena.collision_pairs() 
+        {
+            g2d::draw_line(centres[i], centres[j]);
+        }
+    }
+
+    fn death_message(cause: frogger::DeathCause) -> String 
+    {
+        match cause 
+        {
+            frogger::DeathCause::Squashed => "Squashed by a vehicle!",
+            frogger::DeathCause::Drowned => "Drowned!",
+            frogger::DeathCause::Eaten => "Eaten by a crocodile!",
+            frogger::DeathCause::TimedOut => "Time is up!",
+            frogger::DeathCause::MissedHome => "Missed the home!"
+        }.to_string()
+    }
+
+    /// Draw the background, the actors and the frogs in the home slots.
+    fn draw_board(view: &stream::View) 
+    {
+        g2d::draw_image_clip("frogger-bg.png".to_string(), pt2d::pt(0, 0), pt2d::pt(0, 0), pt2d::pt(640, 480));
+
+        for b in &view.actors 
+        {
+            if let Some(img) = b.sprite 
+            {
+                g2d::draw_image_clip("frogger.png".to_string(), b.pos, img, b.size);
+            }
+        }
+
+        for (owner, (slot, _)) in view.homes.iter().zip(frogger::home_slots()) 
+        {
+            if let Some(player) = owner 
+            {
+                let (sprite, size) = frogger::win_sprite(*player);
+                g2d::draw_image_clip("frogger.png".to_string(), slot + pt2d::pt(4, -4), sprite, size);
+            }
+        }
+    }
+
+    /// Scores, lives and time bars of two players on the board at the same time, on the left and right side.
+    fn draw_hud_simultaneous(view: &stream::View) 
+    {
+        let size = view.size;
+
+        g2d::set_color(255, 255, 255);
+        g2d::draw_text(format!("1UP {:05}", view.scores[0]), pt2d::pt(8, 8), 20);
+        g2d::draw_text_centered(format!("TIME {}", view.time), pt2d::pt(size.x / 2, 18), 20);
+        g2d::draw_text(format!("2UP {:05}", view.scores[1]), pt2d::pt(size.x - 128, 8), 20);
+
+        for player in 0..2 
+        {
+            let right = player == 1;
+            let icon = if right { pt2d::pt(68, 70) } else { pt2d::pt(68, 6) };
+
+            for i in 0..view.lives[player] 
+            {
+                let x = if right { size.x - 32 - i * 28 } else { 8 + i * 28 };
+                g2d::draw_image_clip("frogger.png".to_string(), pt2d::pt(x, size.y - 20), icon, pt2d::pt(24, 18));
+            }
+
+            let ratio = view.timers[player] as f64 / 1000.0;
+            let width = (120.0 * ratio) as i32;
+            let x = if right { size.x - 100 - width } else { 100 };
+
+            if ratio < 0.2 { g2d::set_color(255, 0, 0); } else { g2d::set_color(0, 255, 0); }
+            g2d::fill_rect(pt2d::pt(x, size.y - 16), pt2d::pt(width, 12));
+        }
+    }
+
+    fn draw_hud(view: &stream::View) 
+    {
+        let size = view.size;
+
+        if view.simultaneous() 
+        { 
+            FroggerGui::draw_hud_simultaneous(view); 
+            return; 
+        }
+
+        g2d::set_color(255, 255, 255);
+        g2d::draw_text(format!("SCORE {:05}", view.scores[view.turn]), pt2d::pt(8, 8), 20);
+        g2d::draw_text_centered(format!("TIME {}", view.time), pt2d::pt(size.x - 80, 18), 20);
+
+        if view.scores.len() > 1 
+        {
+            g2d::set_color(255, 255, 0);
+            g2d::draw_text_centered(format!("PLAYER {}", view.turn + 1), pt2d::pt(size.x / 2, 18), 20);
+        }
+
+        // Remaining lives, as little frogs in the bottom left corner
+        for i in 0..view.lives[view.turn] 
+        {
+            g2d::draw_image_clip("frogger.png".to_string(), pt2d::pt(8 + i * 28, size.y - 20), pt2d::pt(68, 6), pt2d::pt(24, 18));
+        }
+
+        // Time left to reach a home slot, as a bar in the bottom right corner
+        let ratio = view.timers[view.turn] as f64 / 1000.0;
+        let width = (200.0 * ratio) as i32;
+
+        if ratio < 0.2 { g2d::set_color(255, 0, 0); } else { g2d::set_color(0, 255, 0); }
+        g2d::fill_rect(pt2d::pt(size.x - 8 - width, size.y - 16), pt2d::pt(width, 12));
+    }
+
+    fn draw_message(size: pt2d::Pt, title: String, subtitle: String) 
+    {
+        g2d::set_color(0, 0, 0);
+        g2d::fill_rect(pt2d::pt(size.x / 2 - 200, size.y / 2 - 60), pt2d::pt(400, 120));
+        g2d::set_color(255, 255, 255);
+        g2d::draw_rect(pt2d::pt(size.x / 2 - 200, size.y / 2 - 60), pt2d::pt(400, 120));
+        g2d::draw_text_centered(title, pt2d::pt(size.x / 2, size.y / 2 - 20), 32);
+        g2d::draw_text_centered(subtitle, pt2d::pt(size.x / 2, size.y / 2 + 25), 18);
+    }
+
+    /// Messages shown over the board in each phase of the game.
+    fn draw_phase(view: &stream::View) 
+    {
+        let size = view.size;
+
+        match view.phase 
+        {
+            frogger::Phase::Ready => 
             {
-                g2d::draw_image_clip("frogger.png".to_string(), b.pos(), img, b.size());
+                g2d::set_color(255, 255, 0);
+                let txt = if view.scores.len() > 1 && !view.simultaneous() { format!("Player {} - Level {} - Get ready!", view.turn + 1, view.level) }
+                          else { format!("Level {} - Get ready!", view.level) };
+                g2d::draw_text_centered(txt, pt2d::pt(size.x / 2, size.y / 2), 28);
             }
+            frogger::Phase::Playing => { }
+            frogger::Phase::Dying => 
+            {
+                if let Some(cause) = view.death 
+                {
+                    g2d::set_color(255, 255, 0);
+                    g2d::draw_text_centered(FroggerGui::death_message(cause), pt2d::pt(size.x / 2, size.y / 2), 28);
+                }
+            }
+            frogger::Phase::LevelClear => 
+            {
+                FroggerGui::draw_message(size, format!("Level {} complete!", view.level),
+                                         format!("Score: {} - Time: {}", view.scores[view.turn], view.time));
+            }
+            frogger::Phase::GameOver | frogger::Phase::Victory => 
+            {
+                let scores: Vec<String> = view.scores.iter().map(|s| s.to_string()).collect();
+                let title = match (view.winner, view.phase) 
+                {
+                    (Some(p), _) if view.mode == frogger::Mode::Versus => format!("Player {} wins!", p + 1),
+                    (_, frogger::Phase::Victory) => "You won!".to_string(),
+                    _ => "Game over".to_string()
+                };
+
+                FroggerGui::draw_message(size, title, format!("Score: {} - Press Enter to play again", scores.join(" / ")));
+            }
+        }
+    }
+
+    /// Draw a game broadcast by someone else, or wait for its stream to begin.
+    fn draw_spectated(&self) 
+    {
+        g2d::clear_canvas();
 
-            if let Some(hero) = b.as_any().downcast_ref::<frogger::Frog>() 
+        match &self.spectated 
+        {
+            Some(view) => 
             {
-                let iter = hero.get_wins().iter();
+                FroggerGui::draw_board(view);
+                FroggerGui::draw_hud(view);
+                FroggerGui::draw_phase(view);
+                g2d::set_color(255, 0, 0);
+                g2d::draw_text_centered("LIVE".to_string(), pt2d::pt(view.size.x / 2, 45), 16);
+            }
+            None => FroggerGui::draw_message(self.game.size(), "Spectator".to_string(), "Waiting for the stream...".to_string())
+        }
+    }
+
+    /// The high-score table, in a box over the board.
+    fn draw_scores(&self) 
+    {
+        let size = self.game.size();
+        let top = pt2d::pt(size.x / 2 - 240, 70);
+        let entries = self.high_scores.as_ref().map_or(vec![], |t| t.entries().clone());
+
+        g2d::set_color(0, 0, 0);
+        g2d::fill_rect(top, pt2d::pt(480, 340));
+        g2d::set_color(255, 255, 255);
+        g2d::draw_rect(top, pt2d::pt(480, 340));
+        g2d::draw_text_centered("High scores".to_string(), pt2d::pt(size.x / 2, top.y + 25), 28);
+
+        if entries.is_empty() 
+        {
+            g2d::draw_text_centered("No results yet".to_string(), pt2d::pt(size.x / 2, top.y + 150), 18);
+        }
+
+        for (i, e) in entries.iter().enumerate() 
+        {
+            let y = top.y + 55 + i as i32 * 24;
+            g2d::set_color(255, 255, if i == 0 { 0 } else { 255 });
+            g2d::draw_text(format!("{:2}. {}", i + 1, e.name), pt2d::pt(top.x + 20, y), 18);
+            g2d::draw_text(format!("{:05}  L{}  {}s  {}", e.score, e.level, e.time, e.date), pt2d::pt(top.x + 220, y), 18);
+        }
+
+        g2d::set_color(255, 255, 255);
+        g2d::draw_text_centered("Press Enter to play".to_string(), pt2d::pt(size.x / 2, top.y + 320), 16);
+    }
+
+    fn draw(&self) 
+    {
+        let view = stream::View::of(&self.game);
+        let size = view.size;
+
+        g2d::clear_canvas();
+        FroggerGui::draw_board(&view);
+
+        if self.debug { self.draw_debug(); }
 
-                iter.fold(0, 
-                          |idx, val| if *val == true
-                                                    {
-                                                        if idx == 0 { g2d::draw_image_clip("frogger.png".to_string(), pt2d::pt(52, 54), hero.get_win_sprite(), hero.get_win_sprite_size()); }
-                                                        if idx == 1 { g2d::draw_image_clip("frogger.png".to_string(), pt2d::pt(180, 54), hero.get_win_sprite(), hero.get_win_sprite_size()); }
-                                                        if idx == 2 { g2d::draw_image_clip("frogger.png".to_string(), pt2d::pt(308, 54), hero.get_win_sprite(), hero.get_win_sprite_size()); }
-                                                        if idx == 3 { g2d::draw_image_clip("frogger.png".to_string(), pt2d::pt(436, 54), hero.get_win_sprite(), hero.get_win_sprite_size()); }
-                                                        if idx == 4 { g2d::draw_image_clip("frogger.png".to_string(), pt2d::pt(564, 54), hero.get_win_sprite(), hero.get_win_sprite_size()); }
-                                                        idx + 1
-                                                    }
-                                                    else { idx + 1 }
-                         );
+        match self.screen 
+        {
+            Screen::Title => 
+            {
+                FroggerGui::draw_message(size, String::new(), "Enter: start - 2: two players - C: co-op - V: versus".to_string());
+                g2d::draw_image_clip("frogger.png".to_string(), pt2d::pt(size.x / 2 - 96, size.y / 2 - 45), pt2d::pt(0, 256), pt2d::pt(192, 32));
+                g2d::set_color(255, 255, 255);
+                g2d::draw_text_centered("H: high scores - M: sound on/off - +/-: volume".to_string(), pt2d::pt(size.x / 2, size.y / 2 + 80), 16);
+            }
+            Screen::Scores => self.draw_scores(),
+            Screen::Paused => 
+            {
+                FroggerGui::draw_hud(&view);
+                FroggerGui::draw_message(size, "Paused".to_string(), "Press P to resume".to_string());
+            }
+            Screen::EnterName(player) => 
+            {
+                FroggerGui::draw_hud(&view);
+                let who = if self.players > 1 { format!("Player {} - ", player + 1) } else { String::new() };
+                FroggerGui::draw_message(size, "New high score!".to_string(), format!("{}Your name: {}_", who, self.name));
+            }
+            Screen::Playing => 
+            {
+                FroggerGui::draw_hud(&view);
+                FroggerGui::draw_phase(&view);
             }
+            Screen::Demo => 
+            {
+                FroggerGui::draw_hud(&view);
+                FroggerGui::draw_phase(&view);
+                g2d::set_color(255, 255, 0);
+                g2d::draw_text_centered("DEMO - Press any key".to_string(), pt2d::pt(size.x / 2, 45), 16);
+            }
+        }
+
+        if self.volume_notice > 0 
+        {
+            let audio = &self.settings.audio;
+            let txt = if audio.muted { "Sound off".to_string() } else { format!("Volume {}%", (audio.master * 100.0).round()) };
+            g2d::set_color(255, 255, 255);
+            g2d::draw_text_centered(txt, pt2d::pt(size.x / 2, size.y - 40), 16);
         }
+    }
+
+    /// Follow a game broadcast by someone else, given the next line of its stream, instead of playing.
+    /// An empty line only enters the spectator mode.
+    pub fn spectate(&mut self, line: &str) -> Result<(), String> 
+    {
+        let decoder = self.spectator.get_or_insert_with(stream::StreamDecoder::new);
 
-        let txt = format!("Lives: {} Time: {}", self.game.remaining_lives(), self.game.playing_time());
-        g2d::set_color(255, 0, 0);
-        g2d::draw_text(txt, pt2d::pt(0, 0), 24);
+        if line.trim().is_empty() { return Ok(()); }
+        if let Some(view) = decoder.decode(line)? { self.spectated = Some(view); }
+        Ok(())
+    }
+
+    /// Submit the results entered in the high-score table to a leaderboard, given the URL of its scores API.
+    pub fn set_leaderboard(&mut self, url: &str) { self.leaderboard = Some(url.to_string()); }
+
+    /// Publish the games played from now on to a relay, given its URL for publishers, e.g. `http://127.0.0.1:7780/`.
+    pub fn broadcast(&mut self, url: &str) 
+    {
+        self.broadcast = Some((url.to_string(), stream::Broadcast::new()));
+    }
 
-        if self.game.game_over() 
+    pub fn tick(&mut self) 
+    {
+        if self.spectator.is_some() 
         {
-            g2d::alert("Game over".to_string());
-            g2d::close_canvas();
+            self.draw_spectated();
+            return;
         }
-        else if self.game.game_won()
+
+        self.input.update_with_presses(input::parse_keys(&g2d::current_keys()), input::parse_key_list(&g2d::pressed_keys()));
+
+        // Taps and swipes, with the mouse or on touch screens
+        let pointer_down = self.input.pressed(input::Key::Mouse(0));
+
+        if !pointer_down && self.input.just_pressed(input::Key::Mouse(0)) 
         {
-            g2d::alert(format!("You won! Remaining lives: {} - Elapsed time: {}", self.game.remaining_lives(), self.game.playing_time()));
-            g2d::close_canvas();
+            // Pressed and released between two ticks
+            self.gestures.update(true, g2d::mouse_pos(), self.game.frog_centre());
         }
-        else 
+
+        let gesture = self.gestures.update(pointer_down, g2d::mouse_pos(), self.game.frog_centre());
+
+        if let Some(action) = gesture 
         {
-            self.game.tick(g2d::current_keys());  // Game logic
+            let over = self.game.game_over() || self.game.game_won();
+            let menu = matches!(self.screen, Screen::Title | Screen::Scores | Screen::EnterName(_) | Screen::Demo);
+            self.input.trigger(if menu || over { input::Action::Restart } else { action });
         }
+
+        let keys = self.input.clone();
+
+        if keys.just_pressed(input::Key::Function(2)) { self.debug = !self.debug; }
+
+        // Letters and digits are part of the names typed for the high-score table
+        if !matches!(self.screen, Screen::EnterName(_)) 
+        {
+            let step = if keys.just_pressed(input::Key::Char('+')) || keys.just_pressed(input::Key::Char('=')) { 0.1 }
+                       else if keys.just_pressed(input::Key::Char('-')) { -0.1 }
+                       else { 0.0 };
+
+            if keys.just_pressed(input::Key::Char('m')) { let _ = self.change_audio(|a| { a.muted = !a.muted; Ok(()) }); }
+            if step != 0.0 { let _ = self.change_audio(|a| a.set_volume("master", a.master + step)); }
+        }
+        self.volume_notice = (self.volume_notice - 1).max(0);
+
+        let over = self.game.game_over() || self.game.game_won();
+
+        match self.screen 
+        {
+            Screen::Title => 
+            {
+                let modes = [('2', frogger::Mode::Turns), ('c', frogger::Mode::Coop), ('v', frogger::Mode::Versus)];
+                let two_players = modes.iter().find(|(c, _)| keys.just_pressed(input::Key::Char(*c)));
+
+                if two_players.is_some() || keys.action_just_pressed(input::Action::Restart) 
+                { 
+                    self.players = if two_players.is_some() { 2 } else { 1 };
+                    self.mode = two_players.map_or(frogger::Mode::Turns, |(_, mode)| *mode);
+                    self.restart(); 
+                }
+                else if keys.just_pressed(input::Key::Char('h')) 
+                { 
+                    self.high_scores();
+                    self.screen = Screen::Scores; 
+                }
+                else if keys.any_just_pressed() || keys.action_just_pressed(input::Action::Restart) { self.idle = 0; }
+                else 
+                {
+                    self.idle += 1;
+                    if self.idle >= DEMO_IDLE_TICKS { self.start_demo(); }
+                }
+            }
+            Screen::Demo => 
+            {
+                if keys.any_just_pressed() || keys.action_just_pressed(input::Action::Restart) { self.stop_demo(); }
+                else if let Some((bot, input)) = &mut self.demo 
+                {
+                    let actions = replay::InputSource::actions(bot, &self.game);
+                    replay::tick_with_actions(&mut self.game, input, &actions);
+
+                    if over || self.game.ticks() >= DEMO_TICKS { self.stop_demo(); }
+                }
+            }
+            Screen::Playing if over && !self.recorded => self.record_results(),
+            Screen::Playing if over => 
+            {
+                if keys.action_just_pressed(input::Action::Restart) { self.restart(); }
+            }
+            Screen::EnterName(player) => 
+            {
+                // Letters and digits only: Space and Enter confirm the name, Escape skips it
+                for key in keys.presses() 
+                {
+                    match key 
+                    {
+                        input::Key::Char(c) if c.is_ascii_alphanumeric() && self.name.len() < scores::MAX_NAME_LEN => self.name.push(c.to_ascii_uppercase()),
+                        input::Key::Backspace => { self.name.pop(); }
+                        _ => { }
+                    }
+                }
+
+                if keys.action_just_pressed(input::Action::Restart) 
+                { 
+                    self.save_result(player);
+                    self.next_name(); 
+                }
+                else if keys.just_pressed(input::Key::Escape) { self.next_name(); }
+            }
+            Screen::Scores => 
+            {
+                if keys.action_just_pressed(input::Action::Restart) { self.restart(); }
+                else if keys.just_pressed(input::Key::Escape) { self.screen = Screen::Title; }
+            }
+            Screen::Playing => 
+            {
+                if keys.action_just_pressed(input::Action::Pause) { self.screen = Screen::Paused; }
+                else 
+                { 
+                    self.replay.record(&self.game, &self.input);
+                    self.game.tick(&self.input);  // Game logic
+                    self.audio.play_all(self.game.sounds());
+                }
+            }
+            Screen::Paused => 
+            {
+                if keys.action_just_pressed(input::Action::Pause) { self.screen = Screen::Playing; }
+            }
+        }
+
+        if let Some((url, broadcast)) = &mut self.broadcast 
+        {
+            if let Some(lines) = broadcast.push(&stream::View::of(&self.game)) { g2d::post_data(url.clone(), lines); }
+        }
+
+        self.music.update(Some(self.track()), self.settings.audio.music_volume());
+        self.draw();
     }
 }
 
@@ -90,6 +707,74 @@ pub fn tick()
     });
 }
 
+/// Change the controls of the game, given a preset name (arrows, wasd, vim) or a bindings configuration.
+/// The controls are saved with the other settings.
+#[wasm_bindgen]
+pub fn set_key_bindings(config: String) -> Result<(), JsValue> 
+{
+    let bindings = input::KeyBindings::from_config(&config).map_err(|e| JsValue::from_str(&e))?;
+
+    GUI.with(|g| {
+        let mut gui = g.borrow_mut();
+        gui.set_key_bindings(bindings);
+        gui.save_key_bindings(0, &config);
+    });
+
+    Ok(())
+}
+
+/// Change the controls of a player when two players play at the same time; the second one uses WASD by default.
+#[wasm_bindgen]
+pub fn set_player_key_bindings(player: usize, config: String) -> Result<(), JsValue> 
+{
+    let bindings = input::KeyBindings::from_config(&config).map_err(|e| JsValue::from_str(&e))?;
+
+    GUI.with(|g| {
+        let mut gui = g.borrow_mut();
+        gui.set_player_key_bindings(player, bindings)?;
+        gui.save_key_bindings(player, &config);
+        Ok(())
+    }).map_err(|e: String| JsValue::from_str(&e))
+}
+
+/// Change a volume (master, music or effects) from 0.0 to 1.0, and save it with the other settings.
+#[wasm_bindgen]
+pub fn set_volume(name: String, volume: f64) -> Result<(), JsValue> 
+{
+    GUI.with(|g| g.borrow_mut().change_audio(|a| a.set_volume(&name, volume))).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Turn all the sounds off or on again, and save the choice with the other settings.
+#[wasm_bindgen]
+pub fn set_muted(muted: bool) 
+{
+    GUI.with(|g| {
+        let _ = g.borrow_mut().change_audio(|a| { a.muted = muted; Ok(()) });
+    });
+}
+
+/// Show a game broadcast by someone else instead of playing, given the lines of its stream one at a time.
+#[wasm_bindgen]
+pub fn spectate(line: String) -> Result<(), JsValue> 
+{
+    GUI.with(|g| g.borrow_mut().spectate(&line)).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Submit the results entered in the high-score table to a leaderboard, e.g. `api/scores` when the page
+/// is served by the leaderboard server.
+#[wasm_bindgen]
+pub fn set_leaderboard(url: String) 
+{
+    GUI.with(|g| g.borrow_mut().set_leaderboard(&url));
+}
+
+/// Publish the games played to a relay, for spectators to watch them, given the URL of the relay for publishers.
+#[wasm_bindgen]
+pub fn broadcast(url: String) 
+{
+    GUI.with(|g| g.borrow_mut().broadcast(&url));
+}
+
 #[wasm_bindgen]
 pub fn setup() {
     GUI.with(|g| {
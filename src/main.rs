@@ -1,22 +1,160 @@
-pub mod actor;
-pub mod frogger;
-pub mod pt2d;
-pub mod rand;
+//! Play many games headless and print statistics about them, to see how changes of the game balance
+//! play out. Run `hello-wasm SOURCE [GAMES] [FIRST_SEED] [TICKS] [LEVEL]`, where `SOURCE` moves the frog:
+//! `random` for a random hop every few ticks, or `easy`, `normal` or `hard` for the bot. Games are
+//! played with seeds `FIRST_SEED` onwards, for `TICKS` ticks at most. `hello-wasm replay FILE...`
+//! plays recorded games instead, with the settings of each replay.
 
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::process;
+use std::time::{Duration, Instant};
 
-fn main() 
+use hello_wasm::bot::*;
+use hello_wasm::frogger::*;
+use hello_wasm::input::*;
+use hello_wasm::replay::*;
+
+const USAGE: &str = "Usage: hello-wasm random|easy|normal|hard [GAMES] [FIRST_SEED] [TICKS] [LEVEL]\n       hello-wasm replay FILE...";
+/// Ticks between two hops of the random source.
+const RANDOM_PERIOD: i32 = 8;
+
+/// What happened in a game.
+struct GameResult
+{
+    seed: u32,
+    score: i32,
+    level: i32,
+    ticks: i32,
+    homes: i32,               // Home slots taken, over all the levels
+    lives: i32,               // Lives played, including the last one if the game is not over
+    deaths: Vec<DeathCause>,
+    elapsed: Duration,        // Time spent in the game logic, without the input source
+    total: Duration
+}
+
+/// Play a game until it is over or for `ticks` ticks at most.
+fn play(config: GameConfig, source: &mut dyn InputSource, ticks: i32) -> GameResult
 {
-    let mut game = frogger::FroggerGame::new(pt2d::pt(480, 360), 3, 2);
+    let mut game = FroggerGame::with_config(config);
+    let mut input = InputState::new();
+    let mut homes = 0;
+    let mut elapsed = Duration::default();
+    let start = Instant::now();
 
-    for _ in 0..100 
+    while game.ticks() < ticks && !game.game_over() && !game.game_won()
     {
-        game.tick(String::new());
+        let taken = game.homes();
+        let actions = source.actions(&game);
+        let tick = Instant::now();
+
+        tick_with_actions(&mut game, &mut input, &actions);
+        elapsed += tick.elapsed();
+
+        homes += game.homes().iter().zip(&taken).filter(|(new, old)| new.is_some() && old.is_none()).count() as i32;
+    }
+
+    let deaths: Vec<DeathCause> = game.stats().deaths().iter().map(|d| d.cause).collect();
+    let alive = !game.game_over();
+
+    GameResult { seed: config.seed,
+                 score: game.score(),
+                 level: game.level(),
+                 ticks: game.ticks(),
+                 homes,
+                 lives: deaths.len() as i32 + alive as i32,
+                 deaths,
+                 elapsed,
+                 total: start.elapsed() }
+}
+
+fn summary(results: &[GameResult])
+{
+    let n = results.len().max(1) as f64;
+    let sum = |f: &dyn Fn(&GameResult) -> i32| results.iter().map(f).sum::<i32>();
+    let (ticks, lives, homes) = (sum(&|r| r.ticks), sum(&|r| r.lives), sum(&|r| r.homes));
+    let deaths: Vec<DeathCause> = results.iter().flat_map(|r| r.deaths.iter().copied()).collect();
+    let elapsed: Duration = results.iter().map(|r| r.elapsed).sum();
+    let total: Duration = results.iter().map(|r| r.total).sum();
+
+    println!();
+    println!("games {}", results.len());
+    println!("average score {:.1}, level {:.2}", sum(&|r| r.score) as f64 / n, sum(&|r| r.level) as f64 / n);
+    println!("average lifetime {:.1} ticks", ticks as f64 / lives.max(1) as f64);
+    println!("homes reached {} ({:.2} per game, {:.2} per life)", homes, homes as f64 / n, homes as f64 / lives.max(1) as f64);
+
+    let mut causes: HashMap<String, usize> = HashMap::new();
+    for cause in &deaths { *causes.entry(format!("{:?}", cause)).or_insert(0) += 1; }
+
+    let mut causes: Vec<(String, usize)> = causes.into_iter().collect();
+    causes.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+
+    println!("deaths {}", deaths.len());
+    for (cause, count) in causes
+    {
+        println!("  {:<12} {:5} ({:.1}%)", cause, count, 100.0 * count as f64 / deaths.len() as f64);
+    }
+
+    let rate = |d: Duration| ticks as f64 / d.as_secs_f64().max(1e-9);
+    println!("throughput {:.0} ticks/s in the game logic, {:.0} ticks/s with the input source", rate(elapsed), rate(total));
+}
 
-        for b in game.actors() 
+fn run(args: &[String]) -> Result<(), String>
+{
+    let source = args.get(1).ok_or(USAGE)?.as_str();
+    let mut results: Vec<GameResult> = vec![];
+
+    if source == "replay"
+    {
+        if args.len() < 3 { return Err(USAGE.to_string()); }
+
+        for path in &args[2..]
+        {
+            let text = fs::read_to_string(path).map_err(|e| format!("Cannot read {}: {}", path, e))?;
+            let replay = Replay::from_text(&text).map_err(|e| format!("{}: {}", path, e))?;
+            results.push(play(replay.config, &mut ReplayInput::new(&replay), replay.ticks));
+        }
+    }
+    else
+    {
+        let bot = if source == "random" { None } else { Some(BotConfig::preset(source).ok_or(format!("Unknown input source {}\n{}", source, USAGE))?) };
+        let number = |i: usize, default: i32| -> Result<i32, String>
+        {
+            args.get(i).map_or(Ok(default), |arg| arg.parse::<i32>().map_err(|e| format!("Bad number {}: {}", arg, e)))
+        };
+        let first = args.get(3).map_or(Ok(1), |arg| arg.parse::<u32>().map_err(|e| format!("Bad seed {}: {}", arg, e)))?;
+        let (games, ticks) = (number(2, 10)?, number(4, 3000)?);
+        let config = GameConfig { level: number(5, GameConfig::default().level)?, players: 1, ..GameConfig::default() };
+
+        // Seeds past the largest one are not played
+        for seed in (first..=u32::MAX).take(games.max(0) as usize)
         {
-            println!("{:?}", b.pos());
+            let mut source: Box<dyn InputSource> = match bot
+            {
+                Some(bot) => Box::new(Bot::new(bot, 0)),
+                None => Box::new(RandomInput::new(seed, RANDOM_PERIOD))
+            };
+            results.push(play(GameConfig { seed, ..config }, &mut *source, ticks));
         }
-        
-        println!();
+    }
+
+    for r in &results
+    {
+        let deaths: Vec<String> = r.deaths.iter().map(|d| format!("{:?}", d)).collect();
+        println!("seed {}: score {} level {} homes {} ticks {} deaths [{}]", r.seed, r.score, r.level, r.homes, r.ticks, deaths.join(", "));
+    }
+
+    summary(&results);
+    Ok(())
+}
+
+fn main()
+{
+    let args: Vec<String> = env::args().collect();
+
+    if let Err(e) = run(&args)
+    {
+        eprintln!("{}", e);
+        process::exit(1);
     }
 }
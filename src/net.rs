@@ -0,0 +1,323 @@
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::mpsc::{channel, Receiver, Sender, TryRecvError};
+
+use crate::frogger::*;
+use crate::input::*;
+use crate::replay::tick_with_actions;
+
+/// Actions a player pressed during a tick, with the hash of the state the tick starts from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InputFrame
+{
+    pub tick: u32,
+    pub player: usize,
+    pub hash: u64,
+    pub actions: Vec<Action>
+}
+impl InputFrame
+{
+    /// Encode the frame as a single line: `tick player hash actions`, with comma separated actions or `-`.
+    pub fn to_line(&self) -> String
+    {
+        let actions: Vec<String> = self.actions.iter().map(|a| format!("{:?}", a)).collect();
+        let actions = if actions.is_empty() { "-".to_string() } else { actions.join(",") };
+
+        format!("{} {} {:016x} {}", self.tick, self.player, self.hash, actions)
+    }
+
+    pub fn from_line(line: &str) -> Result<InputFrame, String>
+    {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+
+        if fields.len() != 4 { return Err(format!("Malformed frame: {}", line)); }
+
+        let tick = fields[0].parse::<u32>().map_err(|e| format!("Bad tick in {}: {}", line, e))?;
+        let player = fields[1].parse::<usize>().map_err(|e| format!("Bad player in {}: {}", line, e))?;
+        let hash = u64::from_str_radix(fields[2], 16).map_err(|e| format!("Bad hash in {}: {}", line, e))?;
+        let mut actions: Vec<Action> = vec![];
+
+        for name in fields[3].split(',').filter(|n| *n != "-")
+        {
+            actions.push(Action::from_name(name).ok_or(format!("Unknown action: {}", name))?);
+        }
+
+        Ok(InputFrame { tick, player, hash, actions })
+    }
+}
+
+/// Channel between peers, carrying the input frames of each tick.
+pub trait Transport
+{
+    /// Send a frame to all the other peers.
+    fn send(&mut self, frame: &InputFrame) -> Result<(), String>;
+
+    /// Frames arrived since the previous call, without waiting for more.
+    fn receive(&mut self) -> Result<Vec<InputFrame>, String>;
+}
+
+/// In-process transport, connecting two peers living in the same program.
+pub struct LoopbackTransport
+{
+    outgoing: Sender<InputFrame>,
+    incoming: Receiver<InputFrame>
+}
+impl LoopbackTransport
+{
+    /// Two transports connected to each other.
+    pub fn pair() -> (LoopbackTransport, LoopbackTransport)
+    {
+        let (tx1, rx1) = channel();
+        let (tx2, rx2) = channel();
+
+        (LoopbackTransport { outgoing: tx1, incoming: rx2 }, LoopbackTransport { outgoing: tx2, incoming: rx1 })
+    }
+}
+impl Transport for LoopbackTransport
+{
+    fn send(&mut self, frame: &InputFrame) -> Result<(), String>
+    {
+        self.outgoing.send(frame.clone()).map_err(|_| "Peer disconnected".to_string())
+    }
+
+    fn receive(&mut self) -> Result<Vec<InputFrame>, String>
+    {
+        let mut frames: Vec<InputFrame> = vec![];
+
+        loop
+        {
+            match self.incoming.try_recv()
+            {
+                Ok(frame) => frames.push(frame),
+                Err(TryRecvError::Empty) => return Ok(frames),
+                Err(TryRecvError::Disconnected) => return Err("Peer disconnected".to_string())
+            }
+        }
+    }
+}
+
+/// Transport over a TCP connection with a single peer, one frame per line.
+pub struct TcpTransport
+{
+    stream: TcpStream,
+    buffer: Vec<u8>,
+    error: Option<String>  // Malformed line, reported once the frames received before it are delivered
+}
+impl TcpTransport
+{
+    fn with_stream(stream: TcpStream) -> Result<TcpTransport, String>
+    {
+        stream.set_nodelay(true).map_err(|e| e.to_string())?;
+        stream.set_nonblocking(true).map_err(|e| e.to_string())?;
+
+        Ok(TcpTransport { stream, buffer: vec![], error: None })
+    }
+
+    /// Wait for a peer to connect at the given address, e.g. `127.0.0.1:7777`.
+    pub fn listen(addr: impl ToSocketAddrs) -> Result<TcpTransport, String>
+    {
+        let listener = TcpListener::bind(addr).map_err(|e| e.to_string())?;
+        let (stream, _) = listener.accept().map_err(|e| e.to_string())?;
+
+        TcpTransport::with_stream(stream)
+    }
+
+    pub fn connect(addr: impl ToSocketAddrs) -> Result<TcpTransport, String>
+    {
+        let stream = TcpStream::connect(addr).map_err(|e| e.to_string())?;
+
+        TcpTransport::with_stream(stream)
+    }
+}
+impl Transport for TcpTransport
+{
+    fn send(&mut self, frame: &InputFrame) -> Result<(), String>
+    {
+        let line = frame.to_line() + "\n";
+        let mut data = line.as_bytes();
+
+        // The stream does not block: retry until the whole line is written
+        while !data.is_empty()
+        {
+            match self.stream.write(data)
+            {
+                Ok(0) => return Err("Peer disconnected".to_string()),
+                Ok(n) => data = &data[n..],
+                Err(e) if e.kind() == ErrorKind::WouldBlock => std::thread::yield_now(),
+                Err(e) => return Err(e.to_string())
+            }
+        }
+
+        Ok(())
+    }
+
+    fn receive(&mut self) -> Result<Vec<InputFrame>, String>
+    {
+        if let Some(e) = self.error.take() { return Err(e); }
+
+        let mut chunk = [0u8; 1024];
+        let mut closed = false;
+
+        loop
+        {
+            match self.stream.read(&mut chunk)
+            {
+                Ok(0) => 
+                { 
+                    closed = true; 
+                    break; 
+                }
+                Ok(n) => self.buffer.extend_from_slice(&chunk[..n]),
+                Err(e) if e.kind() == ErrorKind::WouldBlock => break,
+                Err(e) => return Err(e.to_string())
+            }
+        }
+
+        let mut frames: Vec<InputFrame> = vec![];
+
+        while let Some(end) = self.buffer.iter().position(|b| *b == b'\n')
+        {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+
+            match InputFrame::from_line(&String::from_utf8_lossy(&line))
+            {
+                Ok(frame) => frames.push(frame),
+                Err(e) => if self.error.is_none() { self.error = Some(e); }
+            }
+        }
+
+        // Frames sent before disconnecting or before a malformed line are still delivered
+        if frames.is_empty()
+        {
+            if let Some(e) = self.error.take() { return Err(e); }
+            if closed { return Err("Peer disconnected".to_string()); }
+        }
+
+        Ok(frames)
+    }
+}
+
+/// FNV-1a hash of the state of the game: phase, level, players, actors, random generator and the
+/// hidden state of the frogs. Peers simulating the same ticks from the same configuration get the same hash.
+pub fn state_hash(game: &FroggerGame) -> u64
+{
+    let mut values: Vec<i64> = vec![game.phase() as i64, game.phase_ticks() as i64, game.level() as i64, game.turn() as i64,
+                                    game.arena().rng().state() as i64];
+
+    for p in 0..game.players().len()
+    {
+        values.extend([game.player_score(p) as i64, game.player_lives(p) as i64].iter());
+    }
+
+    for b in game.actors()
+    {
+        let sprite = b.sprite().map_or((-1, -1), |s| (s.x, s.y));
+        values.extend([ActorKind::of(&**b) as i64, b.pos().x as i64, b.pos().y as i64,
+                       b.size().x as i64, b.size().y as i64, sprite.0 as i64, sprite.1 as i64].iter());
+    }
+
+    for hero in game.frogs()
+    {
+        values.extend(hero.hidden_state().iter().map(|v| *v as i64));
+    }
+
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+
+    for byte in values.iter().flat_map(|v| v.to_le_bytes().to_vec())
+    {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+
+    hash
+}
+
+/// A game played by a peer for each player, advancing only when the inputs of all the peers for
+/// the tick have arrived. Peers only exchange the actions they press, so they must start from the
+/// same configuration (seed included) for their simulations to match.
+pub struct Lockstep<T: Transport>
+{
+    game: FroggerGame,
+    transport: T,
+    player: usize,
+    tick: u32,
+    sent: Option<InputFrame>,
+    queued: Vec<Action>,
+    received: Vec<InputFrame>,
+    input: InputState,
+    desync: Option<u32>
+}
+impl<T: Transport> Lockstep<T>
+{
+    /// Game of the local `player`, connected to the other peers by `transport`.
+    pub fn new(config: GameConfig, player: usize, transport: T) -> Lockstep<T>
+    {
+        Lockstep { game: FroggerGame::with_config(config),
+                   transport,
+                   player,
+                   tick: 0,
+                   sent: None,
+                   queued: vec![],
+                   received: vec![],
+                   input: InputState::new(),
+                   desync: None }
+    }
+
+    pub fn game(&self) -> &FroggerGame { &self.game }
+    pub fn player(&self) -> usize { self.player }
+
+    /// Number of ticks simulated so far.
+    pub fn tick(&self) -> u32 { self.tick }
+
+    /// First tick whose state differed between peers, if any: from then on the games diverge.
+    pub fn desync(&self) -> Option<u32> { self.desync }
+
+    /// Send the local actions of the current tick, if not already sent, and advance the game if the
+    /// frames of all the other peers arrived. Actions pressed while waiting are sent with the next tick.
+    /// Returns whether the game advanced.
+    pub fn update(&mut self, actions: &[Action]) -> Result<bool, String>
+    {
+        for action in actions
+        {
+            if !self.queued.contains(action) { self.queued.push(*action); }
+        }
+
+        if self.sent.is_none()
+        {
+            let frame = InputFrame { tick: self.tick,
+                                     player: self.player,
+                                     hash: state_hash(&self.game),
+                                     actions: std::mem::take(&mut self.queued) };
+            self.transport.send(&frame)?;
+            self.sent = Some(frame);
+        }
+
+        self.received.extend(self.transport.receive()?);
+
+        let mut frames: Vec<InputFrame> = vec![];
+
+        for p in 0..self.game.players().len()
+        {
+            let frame = if p == self.player { self.sent.clone() }
+                        else { self.received.iter().find(|f| f.tick == self.tick && f.player == p).cloned() };
+
+            match frame
+            {
+                Some(frame) => frames.push(frame),
+                None => return Ok(false)
+            }
+        }
+
+        if self.desync.is_none() && frames.iter().any(|f| f.hash != frames[0].hash) { self.desync = Some(self.tick); }
+
+        let tick = self.tick;
+        let actions: Vec<(usize, Vec<Action>)> = frames.iter().map(|f| (f.player, f.actions.clone())).collect();
+
+        tick_with_actions(&mut self.game, &mut self.input, &actions);
+        self.received.retain(|f| f.tick > tick);
+        self.sent = None;
+        self.tick += 1;
+
+        Ok(true)
+    }
+}
@@ -0,0 +1,199 @@
+use crate::actor::*;
+use crate::frogger::*;
+
+/// Size of the cells of the grids: a frog hop in each direction.
+pub const CELL: Pt = pt(32, 32);
+/// Top of the first row of the grids. Rows are aligned on the hops of the frog, so that each lane
+/// fits in a single row: the starting row of the frog is the last one.
+pub const GRID_TOP: i32 = 17;
+
+/// What a cell of a grid can hold; each one is a separate channel of the grid.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Channel
+{
+    Frog,           // The frog of the player in turn
+    Vehicle,
+    Platform,       // Rafts, and turtles above the water
+    Water,
+    DivingTurtle,   // Turtles under the water
+    Crocodile,
+    HomeFilled      // Home slots already taken
+}
+impl Channel
+{
+    pub const ALL: [Channel; 7] = [Channel::Frog, Channel::Vehicle, Channel::Platform, Channel::Water,
+                                   Channel::DivingTurtle, Channel::Crocodile, Channel::HomeFilled];
+
+    /// Channel of an actor of the board, if it is shown on the grids.
+    fn of(actor: &dyn Actor) -> Option<Channel>
+    {
+        match ActorKind::of(actor)
+        {
+            ActorKind::Vehicle => Some(Channel::Vehicle),
+            ActorKind::Raft => Some(Channel::Platform),
+            ActorKind::Turtle =>
+            {
+                let immersed = actor.as_any().downcast_ref::<Turtle>().is_some_and(|t| t.immersed());
+                Some(if immersed { Channel::DivingTurtle } else { Channel::Platform })
+            }
+            ActorKind::Crocodile => Some(Channel::Crocodile),
+            ActorKind::Water => Some(Channel::Water),
+            ActorKind::Frog | ActorKind::Other => None
+        }
+    }
+}
+
+/// Occupancy grid of shape `[channels][rows][cols]`, stored in this order: 1.0 where something of
+/// the channel overlaps the cell, 0.0 elsewhere.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Grid
+{
+    pub rows: usize,
+    pub cols: usize,
+    pub data: Vec<f32>
+}
+impl Grid
+{
+    fn new(rows: usize, cols: usize) -> Grid
+    {
+        Grid { rows, cols, data: vec![0.0; Channel::ALL.len() * rows * cols] }
+    }
+
+    /// Shape of the grid: channels, rows and columns.
+    pub fn shape(&self) -> [usize; 3] { [Channel::ALL.len(), self.rows, self.cols] }
+
+    pub fn get(&self, channel: Channel, row: usize, col: usize) -> f32
+    {
+        self.data[(channel as usize * self.rows + row) * self.cols + col]
+    }
+
+    fn set(&mut self, channel: Channel, row: i32, col: i32)
+    {
+        if 0 <= row && (row as usize) < self.rows && 0 <= col && (col as usize) < self.cols
+        {
+            let i = (channel as usize * self.rows + row as usize) * self.cols + col as usize;
+            self.data[i] = 1.0;
+        }
+    }
+
+    /// Mark the cells overlapped by the rectangle, given in cells of the board grid shifted by `origin`.
+    /// Overlaps of a few pixels with the rows above and below are ignored.
+    fn fill(&mut self, channel: Channel, pos: Pt, size: Pt, origin: Pt)
+    {
+        let margin = (CELL.y / 4).min((size.y - 1) / 2);
+        let (tl, br) = (pos + pt(0, margin - GRID_TOP), pos + size - pt(1, margin + GRID_TOP + 1));
+
+        for row in tl.y.div_euclid(CELL.y)..=br.y.div_euclid(CELL.y)
+        {
+            for col in tl.x.div_euclid(CELL.x)..=br.x.div_euclid(CELL.x)
+            {
+                self.set(channel, row - origin.y, col - origin.x);
+            }
+        }
+    }
+}
+
+/// Number of rows and columns of the grid covering the whole board.
+pub fn grid_size(game: &FroggerGame) -> Pt
+{
+    pt(game.size().x / CELL.x, (game.size().y - GRID_TOP) / CELL.y)
+}
+
+/// Frog of the player in turn, if on the board and not out of the game.
+fn frog(game: &FroggerGame) -> Option<&Frog>
+{
+    game.frogs().into_iter().find(|hero| hero.player() == game.turn() && !hero.out())
+}
+
+/// Cell (column, row) of the board grid holding the centre of the frog.
+pub fn frog_cell(game: &FroggerGame) -> Option<Pt>
+{
+    frog(game).map(|hero| {
+        let centre = hero.pos() + hero.size() / pt(2, 2) - pt(0, GRID_TOP);
+        pt(centre.x.div_euclid(CELL.x), centre.y.div_euclid(CELL.y))
+    })
+}
+
+/// Grid of `rows` by `cols` cells, whose top left cell is the `origin` cell of the board grid.
+fn grid_from(game: &FroggerGame, origin: Pt, rows: usize, cols: usize) -> Grid
+{
+    let mut grid = Grid::new(rows, cols);
+
+    for actor in game.actors()
+    {
+        if let Some(channel) = Channel::of(&**actor) { grid.fill(channel, actor.pos(), actor.size(), origin); }
+    }
+
+    for ((pos, size), home) in home_slots().iter().zip(game.homes())
+    {
+        if home.is_some() { grid.fill(Channel::HomeFilled, *pos, *size, origin); }
+    }
+
+    if let Some(hero) = frog(game) { grid.fill(Channel::Frog, hero.pos(), hero.size(), origin); }
+
+    grid
+}
+
+/// Lane by column grid of the whole board: 14 rows by 20 columns on the default board, from the
+/// river bank of the homes down to the starting row.
+pub fn board_grid(game: &FroggerGame) -> Grid
+{
+    let size = grid_size(game);
+    grid_from(game, pt(0, 0), size.y as usize, size.x as usize)
+}
+
+/// Grid of `2 * radius.y + 1` rows by `2 * radius.x + 1` columns centred on the frog; cells out of
+/// the board are empty. Centred on the starting row when the frog is not on the board.
+pub fn frog_window(game: &FroggerGame, radius: Pt) -> Grid
+{
+    let size = grid_size(game);
+    let centre = frog_cell(game).unwrap_or(pt(size.x / 2, size.y - 1));
+
+    grid_from(game, centre - radius, 2 * radius.y as usize + 1, 2 * radius.x as usize + 1)
+}
+
+/// Number of values of the feature vector.
+pub fn feature_count(game: &FroggerGame) -> usize
+{
+    10 + 3 * grid_size(game).y as usize
+}
+
+/// Flat feature vector of `feature_count` values:
+/// - 0-4: frog column and row (as fractions of the board grid), time left (as a fraction of the life
+///   time), lives and whether it is hopping (1.0 or 0.0);
+/// - 5-9: whether each home slot is taken;
+/// - then for each row of the board grid, from the top: whether landing in the column left of the
+///   frog, in the same column or in the right one would be deadly right now.
+pub fn features(game: &FroggerGame) -> Vec<f32>
+{
+    let size = grid_size(game);
+    let grid = board_grid(game);
+    let cell = frog_cell(game);
+    let hero = frog(game);
+
+    let mut values: Vec<f32> = vec![cell.map_or(0.0, |c| c.x as f32 / size.x as f32),
+                                    cell.map_or(0.0, |c| c.y as f32 / size.y as f32),
+                                    game.player_time_ratio(game.turn()) as f32,
+                                    game.player_lives(game.turn()) as f32,
+                                    hero.map_or(0.0, |h| h.hopping() as i32 as f32)];
+
+    values.extend(game.homes().iter().map(|h| h.is_some() as i32 as f32));
+
+    let deadly = |row: i32, col: i32| -> f32
+    {
+        if col < 0 || col >= size.x { return 1.0; }
+
+        let has = |channel: Channel| grid.get(channel, row as usize, col as usize) > 0.0;
+        let deadly = has(Channel::Vehicle) || has(Channel::Crocodile) || has(Channel::DivingTurtle)
+                     || (has(Channel::Water) && !has(Channel::Platform));
+        deadly as i32 as f32
+    };
+
+    for row in 0..size.y
+    {
+        let col = cell.map_or(size.x / 2, |c| c.x);
+        values.extend([deadly(row, col - 1), deadly(row, col), deadly(row, col + 1)].iter());
+    }
+
+    values
+}
@@ -9,7 +9,7 @@ pub struct Pt
     pub y: i32
 }
 
-pub fn pt(x: i32, y: i32) -> Pt { Pt{x: x, y: y} }
+pub const fn pt(x: i32, y: i32) -> Pt { Pt{x: x, y: y} }
 
 impl Add for Pt 
 {
@@ -6,6 +6,12 @@ pub struct Rng {
 }
 impl Rng 
 {
+    pub fn new(seed: u32) -> Rng 
+    {
+        // Xorshift would only produce zeros from a zero state
+        Rng{random: if seed == 0 { 0x9E37_79B9 } else { seed }}
+    }
+
     pub fn from_addr() -> Rng 
     {
         let num = vec![1, 2, 3];
@@ -20,6 +26,9 @@ impl Rng
         Rng{random: now.as_millis() as u32}
     }
 
+    /// Internal state, the same for generators that will produce the same numbers.
+    pub fn state(&self) -> u32 { self.random }
+
     pub fn randint(&mut self, nmin: i32, nmax: i32) -> i32 
     {
         // From "Xorshift RNGs" by George Marsaglia
@@ -30,6 +39,13 @@ impl Rng
     }
 }
 
+/// A seed that changes from game to game, for games not meant to be reproduced: drawn from the shared
+/// generator, which advances at each call.
+pub fn random_seed() -> u32
+{
+    randint(1, i32::MAX - 1) as u32
+}
+
 thread_local! 
 {
     static RNG: RefCell<Rng> = RefCell::new(Rng::from_addr());
@@ -0,0 +1,265 @@
+use std::collections::HashSet;
+
+use crate::frogger::*;
+use crate::input::*;
+use crate::pt2d::*;
+use crate::rand::Rng;
+
+/// Hop actions a player pressed during a tick.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReplayFrame
+{
+    pub tick: i32,
+    pub player: usize,
+    pub actions: Vec<Action>
+}
+
+/// Tick the game with the actions of each player, pressing for each action the first key bound to it
+/// for the frog the player moves, in the bindings of `input`. Actions of players not on the board are
+/// ignored. Simulations driven this way do not depend on the keys each player chose.
+pub fn tick_with_actions(game: &mut FroggerGame, input: &mut InputState, actions: &[(usize, Vec<Action>)])
+{
+    let mut presses: Vec<Key> = vec![];
+
+    for (player, player_actions) in actions
+    {
+        let controls = if game.simultaneous() { Some(*player) }
+                       else if *player == game.turn() { Some(0) }
+                       else { None };
+
+        if let Some(bindings) = controls.and_then(|c| input.player_bindings(c))
+        {
+            presses.extend(player_actions.iter().filter_map(|a| bindings.keys(*a).first()));
+        }
+    }
+
+    input.update_with_presses(HashSet::new(), presses);
+    game.tick(input);
+}
+
+/// Where the actions of the players come from, tick by tick: a bot, a replay, random presses.
+pub trait InputSource
+{
+    /// Actions of each player for the tick the game is about to play, as taken by `tick_with_actions`.
+    fn actions(&mut self, game: &FroggerGame) -> Vec<(usize, Vec<Action>)>;
+}
+
+/// Actions recorded in a replay, played back at the ticks they were pressed.
+pub struct ReplayInput
+{
+    frames: Vec<ReplayFrame>,
+    next: usize   // First frame not played yet
+}
+impl ReplayInput
+{
+    pub fn new(replay: &Replay) -> ReplayInput
+    {
+        ReplayInput { frames: replay.frames.clone(), next: 0 }
+    }
+}
+impl InputSource for ReplayInput
+{
+    fn actions(&mut self, game: &FroggerGame) -> Vec<(usize, Vec<Action>)>
+    {
+        let mut actions: Vec<(usize, Vec<Action>)> = vec![];
+
+        while let Some(frame) = self.frames.get(self.next).filter(|f| f.tick <= game.ticks())
+        {
+            if frame.tick == game.ticks() { actions.push((frame.player, frame.actions.clone())); }
+            self.next += 1;
+        }
+
+        actions
+    }
+}
+
+/// A random hop for each player every `period` ticks.
+pub struct RandomInput
+{
+    rng: Rng,
+    period: i32
+}
+impl RandomInput
+{
+    pub fn new(seed: u32, period: i32) -> RandomInput
+    {
+        RandomInput { rng: Rng::new(seed), period: period.max(1) }
+    }
+}
+impl InputSource for RandomInput
+{
+    fn actions(&mut self, game: &FroggerGame) -> Vec<(usize, Vec<Action>)>
+    {
+        if game.ticks() % self.period != 0 { return vec![]; }
+
+        (0..game.players().len()).map(|p| (p, vec![Action::ALL[self.rng.randint(0, 3) as usize]])).collect()
+    }
+}
+
+/// Configuration of a game and the actions of its players at each tick: enough to play it again.
+#[derive(Clone, Debug)]
+pub struct Replay
+{
+    pub config: GameConfig,
+    pub ticks: i32,
+    pub frames: Vec<ReplayFrame>
+}
+impl Replay
+{
+    pub fn new(config: GameConfig) -> Replay
+    {
+        Replay { config, ticks: 0, frames: vec![] }
+    }
+
+    /// Record the hops pressed by each player in the tick the game is about to play.
+    pub fn record(&mut self, game: &FroggerGame, input: &InputState)
+    {
+        let players: Vec<(usize, usize)> = if game.simultaneous() { (0..game.players().len()).map(|p| (p, p)).collect() }
+                                           else { vec![(game.turn(), 0)] };
+
+        for (player, controls) in players
+        {
+            let actions: Vec<Action> = input.player_actions_just_pressed(controls).into_iter().filter(|a| a.direction().is_some()).collect();
+
+            if !actions.is_empty() { self.frames.push(ReplayFrame { tick: game.ticks(), player, actions }); }
+        }
+
+        self.ticks = game.ticks() + 1;
+    }
+
+    /// Check that the game can be played again within reasonable resources, before playing replays
+    /// coming from someone else (see `GameConfig::check`).
+    pub fn check_config(&self) -> Result<(), String>
+    {
+        self.config.check()?;
+        if self.ticks < 0 { return Err(format!("Bad number of ticks: {}", self.ticks)); }
+        Ok(())
+    }
+
+    /// Play the game again from the beginning, returning it as it was after the last tick.
+    pub fn simulate(&self) -> FroggerGame
+    {
+        let mut game = FroggerGame::with_config(self.config);
+        let mut input = InputState::new();
+        let mut source = ReplayInput::new(self);
+
+        for _ in 0..self.ticks
+        {
+            let actions = source.actions(&game);
+            tick_with_actions(&mut game, &mut input, &actions);
+        }
+
+        game
+    }
+
+    /// FNV-1a hash of the configuration, seed included, and of the actions pressed: the same for the
+    /// same game submitted again, whatever the number of ticks played after it.
+    pub fn digest(&self) -> u64
+    {
+        let text = self.to_text();
+        let text: Vec<&str> = text.lines().filter(|line| !line.starts_with("ticks ")).collect();
+
+        text.join("\n").bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3))
+    }
+
+    /// Encode the replay as text: the configuration as `name value` lines, the number of ticks, then a
+    /// `tick player actions` line for each tick a player pressed something.
+    pub fn to_text(&self) -> String
+    {
+        let c = &self.config;
+        let mut text = format!("seed {}\nsize {} {}\nlevel {}\nvehicles {}\nrafts {}\nplayers {}\nmode {:?}\nticks {}\n",
+                               c.seed, c.size.x, c.size.y, c.level, c.n_vehicle_per_row, c.n_raft_per_row, c.players, c.mode, self.ticks);
+
+        for frame in &self.frames
+        {
+            let actions: Vec<String> = frame.actions.iter().map(|a| format!("{:?}", a)).collect();
+            text += &format!("{} {} {}\n", frame.tick, frame.player, actions.join(","));
+        }
+
+        text
+    }
+
+    /// Parse the text written by `to_text`. Empty lines and lines starting with `#` are skipped;
+    /// settings missing from the text keep their default value.
+    pub fn from_text(text: &str) -> Result<Replay, String>
+    {
+        let mut replay = Replay::new(GameConfig::default());
+
+        for line in text.lines().map(|l| l.trim()).filter(|l| !l.is_empty() && !l.starts_with('#'))
+        {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            let number = |i: usize| -> Result<i32, String>
+            {
+                fields.get(i).ok_or(format!("Missing value in: {}", line))?.parse::<i32>().map_err(|e| format!("Bad number in {}: {}", line, e))
+            };
+
+            match fields[0]
+            {
+                "seed" => replay.config.seed = fields.get(1).ok_or(format!("Missing value in: {}", line))?
+                                                     .parse::<u32>().map_err(|e| format!("Bad number in {}: {}", line, e))?,
+                "size" => replay.config.size = pt(number(1)?, number(2)?),
+                "level" => replay.config.level = number(1)?,
+                "vehicles" => replay.config.n_vehicle_per_row = number(1)?,
+                "rafts" => replay.config.n_raft_per_row = number(1)?,
+                "players" => replay.config.players = number(1)?.max(1) as usize,
+                "mode" => replay.config.mode = Mode::from_name(fields.get(1).unwrap_or(&"")).ok_or(format!("Unknown mode in: {}", line))?,
+                "ticks" => replay.ticks = number(1)?,
+                _ =>
+                {
+                    let mut actions: Vec<Action> = vec![];
+
+                    for name in fields.get(2).ok_or(format!("Missing actions in: {}", line))?.split(',')
+                    {
+                        actions.push(Action::from_name(name).ok_or(format!("Unknown action in: {}", line))?);
+                    }
+
+                    replay.frames.push(ReplayFrame { tick: number(0)?, player: number(1)? as usize, actions });
+                }
+            }
+        }
+
+        replay.frames.sort_by_key(|f| (f.tick, f.player));
+        Ok(replay)
+    }
+}
+
+/// Outcome of a game played again from its replay, as reported by `verify`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Verification
+{
+    pub scores: Vec<i32>,              // One for each player
+    pub lives: Vec<i32>,
+    pub levels: Vec<i32>,
+    pub homes: Vec<Option<usize>>,     // Player who took each home slot
+    pub ticks: i32,
+    pub time: i32,                     // Playing time, in seconds
+    pub finished: bool                 // Whether the game was over or won at the last tick
+}
+impl Verification
+{
+    /// Encode the outcome as `name value` lines, with space separated values for each player.
+    pub fn to_text(&self) -> String
+    {
+        let join = |values: &[i32]| values.iter().map(|v| v.to_string()).collect::<Vec<String>>().join(" ");
+        let homes: Vec<i32> = self.homes.iter().map(|h| h.map_or(-1, |p| p as i32)).collect();
+
+        format!("scores {}\nlives {}\nlevels {}\nhomes {}\nticks {}\ntime {}\nfinished {}\n",
+                join(&self.scores), join(&self.lives), join(&self.levels), join(&homes), self.ticks, self.time, self.finished)
+    }
+}
+
+/// Play the replay again, headless, and report the authoritative outcome of the game: results claimed
+/// by a player can be trusted only if they match it.
+pub fn verify(replay: &Replay) -> Verification
+{
+    let game = replay.simulate();
+    let players = 0..game.players().len();
+
+    Verification { scores: players.clone().map(|p| game.player_score(p)).collect(),
+                   lives: players.clone().map(|p| game.player_lives(p)).collect(),
+                   levels: players.map(|p| game.players()[p].level).collect(),
+                   homes: game.homes(),
+                   ticks: game.ticks(),
+                   time: game.playing_time(),
+                   finished: game.game_over() || game.game_won() }
+}
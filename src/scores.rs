@@ -0,0 +1,167 @@
+use std::path::PathBuf;
+
+use crate::g2d;
+
+/// Number of results kept in a high-score table.
+pub const MAX_SCORES: usize = 10;
+/// Longest name accepted, in characters.
+pub const MAX_NAME_LEN: usize = 10;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HighScore
+{
+    pub name: String,
+    pub score: i32,
+    pub level: i32,
+    pub time: i32,     // Playing time, in seconds
+    pub date: String   // As YYYY-MM-DD
+}
+impl HighScore
+{
+    /// Encode the result as a line of tab separated values; tabs and new lines are removed from the name.
+    pub fn to_line(&self) -> String
+    {
+        let name: String = self.name.chars().filter(|c| *c != '\t' && *c != '\n').collect();
+        format!("{}\t{}\t{}\t{}\t{}", name, self.score, self.level, self.time, self.date)
+    }
+
+    pub fn from_line(line: &str) -> Result<HighScore, String>
+    {
+        let fields: Vec<&str> = line.split('\t').collect();
+
+        if fields.len() != 5 { return Err(format!("Malformed score: {}", line)); }
+
+        let number = |i: usize| fields[i].trim().parse::<i32>().map_err(|e| format!("Bad number in {}: {}", line, e));
+
+        Ok(HighScore { name: fields[0].to_string(), score: number(1)?, level: number(2)?, time: number(3)?, date: fields[4].trim().to_string() })
+    }
+}
+
+/// The best results, from the highest score down. Ties are broken by the shorter time, then by the
+/// earlier result.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HighScores
+{
+    entries: Vec<HighScore>
+}
+impl HighScores
+{
+    pub fn new() -> HighScores { HighScores::default() }
+
+    pub fn entries(&self) -> &Vec<HighScore> { &self.entries }
+
+    /// Position the result would get in the table, if good enough to enter it.
+    pub fn rank(&self, score: i32, time: i32) -> Option<usize>
+    {
+        let rank = self.entries.iter().position(|e| score > e.score || (score == e.score && time < e.time)).unwrap_or(self.entries.len());
+        if rank < MAX_SCORES && score > 0 { Some(rank) } else { None }
+    }
+
+    /// Add a result, returning its position in the table if it entered it.
+    pub fn insert(&mut self, entry: HighScore) -> Option<usize>
+    {
+        let rank = self.rank(entry.score, entry.time)?;
+
+        self.entries.insert(rank, entry);
+        self.entries.truncate(MAX_SCORES);
+        Some(rank)
+    }
+
+    /// One result per line, in the syntax of `HighScore::to_line`.
+    pub fn to_text(&self) -> String
+    {
+        self.entries.iter().map(|e| e.to_line() + "\n").collect()
+    }
+
+    /// Parse the results listed by `to_text`, skipping empty lines. Results are sorted again, and
+    /// the worst ones are dropped if too many.
+    pub fn from_text(text: &str) -> Result<HighScores, String>
+    {
+        let mut scores = HighScores::new();
+
+        for line in text.lines().filter(|l| !l.trim().is_empty())
+        {
+            scores.insert(HighScore::from_line(line)?);
+        }
+
+        Ok(scores)
+    }
+}
+
+/// Somewhere to keep the high-score table between games.
+pub trait ScoreStore
+{
+    /// The saved table, empty if nothing was saved yet.
+    fn load(&self) -> Result<HighScores, String>;
+    fn save(&mut self, scores: &HighScores) -> Result<(), String>;
+}
+
+/// Table saved in a text file, for native builds.
+pub struct FileStore
+{
+    path: PathBuf
+}
+impl FileStore
+{
+    pub fn new(path: impl Into<PathBuf>) -> FileStore { FileStore { path: path.into() } }
+}
+impl ScoreStore for FileStore
+{
+    fn load(&self) -> Result<HighScores, String>
+    {
+        match std::fs::read_to_string(&self.path)
+        {
+            Ok(text) => HighScores::from_text(&text),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(HighScores::new()),
+            Err(e) => Err(format!("{}: {}", self.path.display(), e))
+        }
+    }
+
+    fn save(&mut self, scores: &HighScores) -> Result<(), String>
+    {
+        std::fs::write(&self.path, scores.to_text()).map_err(|e| format!("{}: {}", self.path.display(), e))
+    }
+}
+
+/// Table saved in the local storage of the browser, under the given key.
+pub struct LocalStore
+{
+    key: String
+}
+impl LocalStore
+{
+    pub fn new(key: &str) -> LocalStore { LocalStore { key: key.to_string() } }
+}
+impl ScoreStore for LocalStore
+{
+    fn load(&self) -> Result<HighScores, String>
+    {
+        HighScores::from_text(&g2d::load_data(self.key.clone()))
+    }
+
+    fn save(&mut self, scores: &HighScores) -> Result<(), String>
+    {
+        g2d::save_data(self.key.clone(), scores.to_text());
+        Ok(())
+    }
+}
+
+/// Current date as YYYY-MM-DD (UTC), for native builds: in the browser, see `g2d::current_date`.
+pub fn today() -> String
+{
+    use std::time::SystemTime as st;
+    let days = st::now().duration_since(st::UNIX_EPOCH).map_or(0, |d| d.as_secs() / 86400) as i64;
+
+    // From "chrono-Compatible Low-Level Date Algorithms" by Howard Hinnant
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + (month <= 2) as i64;
+
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
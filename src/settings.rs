@@ -0,0 +1,100 @@
+use crate::audio::AudioSettings;
+use crate::g2d;
+use crate::input::{KeyBindings, MAX_PLAYERS};
+
+/// Preferences of the player kept between visits: volumes, mute, and the controls of each player.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Settings
+{
+    pub audio: AudioSettings,
+    pub key_bindings: Vec<(usize, String)>   // Player and configuration, as accepted by `KeyBindings::from_config`
+}
+impl Settings
+{
+    /// Encode the settings as `name value` lines; the key bindings of a player take a single line, with
+    /// `\n` for line breaks and `\\` for backslashes, since any key can be bound.
+    pub fn to_text(&self) -> String
+    {
+        let a = &self.audio;
+        let mut text = format!("master {}\nmusic {}\neffects {}\nmuted {}\n", a.master, a.music, a.effects, a.muted);
+
+        for (player, config) in &self.key_bindings
+        {
+            text += &format!("keys {} {}\n", player, escape(config));
+        }
+
+        text
+    }
+
+    /// Parse the text written by `to_text`; settings missing from the text keep their default value.
+    /// Key bindings of players past `MAX_PLAYERS`, or that cannot be read, are dropped.
+    pub fn from_text(text: &str) -> Result<Settings, String>
+    {
+        let mut settings = Settings::default();
+
+        for line in text.lines().map(|l| l.trim()).filter(|l| !l.is_empty() && !l.starts_with('#'))
+        {
+            let (name, value) = line.split_once(' ').ok_or(format!("Missing value in: {}", line))?;
+            let volume = || value.trim().parse::<f64>().map_err(|e| format!("Bad number in {}: {}", line, e));
+
+            match name
+            {
+                "master" | "music" | "effects" => settings.audio.set_volume(name, volume()?)?,
+                "muted" => settings.audio.muted = value.trim() == "true",
+                "keys" =>
+                {
+                    let (player, config) = value.trim().split_once(' ').ok_or(format!("Missing bindings in: {}", line))?;
+                    let player = player.parse::<usize>().ok().filter(|p| *p < MAX_PLAYERS);
+                    let config = unescape(config);
+
+                    if let (Some(player), Ok(_)) = (player, KeyBindings::from_config(&config)) { settings.set_key_bindings(player, &config); }
+                }
+                _ => return Err(format!("Unknown setting in: {}", line))
+            }
+        }
+
+        Ok(settings)
+    }
+
+    /// Keep the configuration of the controls of a player, replacing the previous one.
+    pub fn set_key_bindings(&mut self, player: usize, config: &str)
+    {
+        self.key_bindings.retain(|(p, _)| *p != player);
+        self.key_bindings.push((player, config.to_string()));
+    }
+
+    /// Settings saved in the local storage of the browser under the given key; the default ones if
+    /// nothing was saved yet, or if what was saved cannot be read.
+    pub fn load(key: &str) -> Settings
+    {
+        Settings::from_text(&g2d::load_data(key.to_string())).unwrap_or_default()
+    }
+
+    pub fn save(&self, key: &str)
+    {
+        g2d::save_data(key.to_string(), self.to_text());
+    }
+}
+
+fn escape(config: &str) -> String
+{
+    config.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape(text: &str) -> String
+{
+    let mut config = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next()
+    {
+        match (c, chars.clone().next())
+        {
+            ('\\', Some('n')) => { config.push('\n'); chars.next(); },
+            ('\\', Some('\\')) => { config.push('\\'); chars.next(); },
+            (c, _) => config.push(c)
+        }
+    }
+
+    config
+}
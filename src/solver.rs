@@ -0,0 +1,173 @@
+use std::collections::HashSet;
+
+use crate::actor::*;
+use crate::frogger::*;
+use crate::input::*;
+use crate::observe::{board_grid, Channel, CELL, GRID_TOP};
+use crate::rand::Rng;
+
+/// Where the frog can get to, or what stops it, for a seed of a level.
+#[derive(Clone, Debug)]
+pub struct SeedReport
+{
+    pub seed: u32,
+    pub reached: Vec<Option<i32>>,   // Ticks needed to reach each home slot, if it can be reached in time
+    pub best_row: i32,               // Highest row reached, in the rows of the observation grids
+    pub blocking: Option<Lane>       // Lane the frog could not cross, if some home slot cannot be reached
+}
+impl SeedReport
+{
+    pub fn solvable(&self) -> bool { self.reached.iter().all(|r| r.is_some()) }
+
+    /// Home slots that cannot be reached, from left to right.
+    pub fn unreachable(&self) -> Vec<usize>
+    {
+        self.reached.iter().enumerate().filter(|(_, r)| r.is_none()).map(|(i, _)| i).collect()
+    }
+}
+
+/// A row of the board, and what moves along it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lane
+{
+    pub row: i32,
+    pub kinds: Vec<ActorKind>   // Empty for the row of the homes
+}
+
+/// Row of the observation grids holding the centre of the rectangle.
+fn row_of(pos: Pt, size: Pt) -> i32 { (pos.y + size.y / 2 - GRID_TOP).div_euclid(CELL.y) }
+
+/// Kinds of the actors moving along a row, besides the water.
+fn lane(actors: &[Box<dyn Actor>], row: i32) -> Lane
+{
+    let mut kinds: Vec<ActorKind> = vec![];
+
+    for actor in actors.iter().filter(|a| row_of(a.pos(), a.size()) == row)
+    {
+        let kind = ActorKind::of(&**actor);
+        if kind != ActorKind::Water && !kinds.contains(&kind) { kinds.push(kind); }
+    }
+
+    Lane { row, kinds }
+}
+
+/// Whether two rectangles are closer than `margin` on both axes.
+fn near(pos1: Pt, size1: Pt, pos2: Pt, size2: Pt, margin: i32) -> bool
+{
+    pos2.x < pos1.x + size1.x + margin && pos1.x < pos2.x + size2.x + margin
+        && pos2.y < pos1.y + size1.y + margin && pos1.y < pos2.y + size2.y + margin
+}
+
+/// Search whether the frog of the first player can reach each home slot within its time, from the
+/// beginning of the level built from the given configuration.
+///
+/// The board is played once without the frog moving, recording the actors at each tick; then every
+/// sequence of waits and hops up, left and right is tried, in order of time, moving a copy of the frog among copies of
+/// the recorded actors near it. Places the frog reaches at the same tick by different sequences are
+/// searched once, and so are the places of the rows without traffic nor water, where it can wait.
+/// Hops down are not tried: they let the frog dodge, but they would make the search much longer.
+pub fn check(config: &GameConfig) -> SeedReport
+{
+    let mut config = *config;
+    config.players = 1;
+    config.mode = Mode::Turns;
+
+    let mut game = FroggerGame::with_config(config);
+    let idle = InputState::new();
+
+    while game.phase() == Phase::Ready { game.tick(&idle); }
+
+    // Rows where nothing moves and there is no water: frogs can wait there as long as they like
+    let grid = board_grid(&game);
+    let safe: Vec<bool> = (0..grid.rows).map(|row| {
+        Channel::ALL.iter().filter(|c| **c != Channel::Frog).all(|c| (0..grid.cols).all(|col| grid.get(*c, row, col) == 0.0))
+    }).collect();
+
+    let start = game.frogs()[0].clone();
+    let life = start.time_left();
+    let mut world: Vec<Vec<Box<dyn Actor>>> = vec![];
+
+    // The frog waits at the start for its whole life: the other actors do not depend on it
+    for _ in 0..life
+    {
+        world.push(game.actors().iter().filter(|a| a.as_any().downcast_ref::<Frog>().is_none()).cloned().collect());
+        game.tick(&idle);
+    }
+
+    let bindings = KeyBindings::default();
+    let moves: Vec<InputState> = [None, Some(Action::Up), Some(Action::Left), Some(Action::Right)].iter().map(|a| {
+        let mut input = InputState::new();
+        input.update_with_presses(HashSet::new(), a.and_then(|a| bindings.keys(a).first().copied()).into_iter().collect());
+        input
+    }).collect();
+    let homes = home_slots().len();
+
+    let mut report = SeedReport { seed: config.seed, reached: vec![None; homes], best_row: row_of(start.pos(), start.size()), blocking: None };
+    let mut frontier: Vec<Frog> = vec![start];
+    let mut waited: HashSet<(i32, i32, i32, i32)> = HashSet::new();
+
+    for (tick, actors) in world.iter().enumerate()
+    {
+        let mut seen: HashSet<(i32, i32, i32, i32, bool)> = HashSet::new();
+        let mut next: Vec<Frog> = vec![];
+
+        for frog in &frontier
+        {
+            let inputs = if frog.hopping() { &moves[..1] } else { &moves[..] };
+
+            for input in inputs
+            {
+                // Only the actors close to the frog can touch it during the tick
+                let mut arena = Arena::new(game.size(), Rng::new(1));
+
+                for actor in actors.iter().filter(|a| near(a.pos(), a.size(), frog.pos(), frog.size(), CELL.x))
+                {
+                    arena.spawn(actor.clone());
+                }
+                arena.spawn(Box::new(frog.clone()));
+
+                arena.tick(input);
+
+                let moved = match arena.actors().last().and_then(|a| a.as_any().downcast_ref::<Frog>())
+                {
+                    Some(moved) if !moved.dying() && moved.died().is_none() => moved,
+                    _ => continue
+                };
+
+                if let Some(slot) = moved.get_wins().iter().position(|w| *w)
+                {
+                    // Taking a slot sends the frog back to the start: this sequence is over
+                    if report.reached[slot].is_none() { report.reached[slot] = Some(tick as i32 + 1); }
+                    continue;
+                }
+
+                report.best_row = report.best_row.min(row_of(moved.pos(), moved.size()));
+
+                // Getting later to a place where the frog could have waited is not worth searching
+                let (pos, size, row) = (moved.pos(), moved.size(), row_of(moved.pos(), moved.size()));
+                let waiting = !moved.hopping() && safe.get(row as usize).copied().unwrap_or(false);
+
+                if waiting && !waited.insert((pos.x, pos.y, size.x, size.y)) { continue; }
+                if seen.insert((pos.x, pos.y, size.x, size.y, moved.hopping())) { next.push(moved.clone()); }
+            }
+        }
+
+        frontier = next;
+        if frontier.is_empty() || report.solvable() { break; }
+    }
+
+    if !report.solvable()
+    {
+        // The frog got stuck below the lane after the highest row it reached, or at the homes
+        let row = if report.reached.iter().any(|r| r.is_some()) { row_of(home_slots()[0].0, home_slots()[0].1) } else { report.best_row - 1 };
+        report.blocking = Some(lane(&world[0], row));
+    }
+
+    report
+}
+
+/// Check each seed in the range, with the other settings of the configuration.
+pub fn check_seeds(config: &GameConfig, seeds: std::ops::RangeInclusive<u32>) -> Vec<SeedReport>
+{
+    seeds.map(|seed| check(&GameConfig { seed, ..*config })).collect()
+}
@@ -0,0 +1,306 @@
+use crate::frogger::*;
+use crate::pt2d::*;
+
+/// Ticks between two full frames of a stream, so that spectators can join at any time.
+pub const KEYFRAME_TICKS: i32 = 90;
+/// Ticks of a stream published together by `Broadcast`, so that browsers make only a few requests a second.
+pub const BATCH_TICKS: usize = 10;
+/// Number of values describing each actor: kind, x, y, width, height, sprite x, sprite y.
+const ACTOR_VALUES: usize = 7;
+
+#[derive(Copy, Clone, Debug)]
+pub struct ActorView
+{
+    pub kind: ActorKind,
+    pub pos: Pt,
+    pub size: Pt,
+    pub sprite: Option<Pt>
+}
+
+/// Everything drawn on the screen during a tick of a game: the board and the HUD.
+#[derive(Clone, Debug)]
+pub struct View
+{
+    pub tick: i32,
+    pub size: Pt,
+    pub phase: Phase,
+    pub level: i32,
+    pub mode: Mode,
+    pub turn: usize,
+    pub time: i32,                     // Playing time, in seconds
+    pub winner: Option<usize>,
+    pub death: Option<DeathCause>,     // Cause of the last death
+    pub scores: Vec<i32>,              // One for each player
+    pub lives: Vec<i32>,
+    pub timers: Vec<i32>,              // Time left to reach a home slot, in thousandths of the life time
+    pub homes: Vec<Option<usize>>,     // Player who took each home slot
+    pub actors: Vec<ActorView>
+}
+impl View
+{
+    pub fn of(game: &FroggerGame) -> View
+    {
+        let players = 0..game.players().len();
+
+        View { tick: game.ticks(),
+               size: game.size(),
+               phase: game.phase(),
+               level: game.level(),
+               mode: game.config().mode,
+               turn: game.turn(),
+               time: game.playing_time(),
+               winner: game.winner(),
+               death: game.stats().deaths().last().map(|d| d.cause),
+               scores: players.clone().map(|p| game.player_score(p)).collect(),
+               lives: players.clone().map(|p| game.player_lives(p)).collect(),
+               timers: players.map(|p| (game.player_time_ratio(p) * 1000.0) as i32).collect(),
+               homes: game.homes(),
+               actors: game.actors().iter().map(|b| ActorView { kind: ActorKind::of(&**b), pos: b.pos(), size: b.size(), sprite: b.sprite() }).collect() }
+    }
+
+    /// Whether the frogs of all the players are on the board at the same time.
+    pub fn simultaneous(&self) -> bool { self.scores.len() > 1 && self.mode != Mode::Turns }
+
+    /// HUD values as integers: the first ones have a fixed position, then come three values for each player
+    /// (score, lives, timer) and one for each home slot. Missing values are -1.
+    fn hud_values(&self) -> Vec<i32>
+    {
+        let index = |i: Option<usize>| i.map_or(-1, |i| i as i32);
+        let mut values = vec![self.size.x, self.size.y, self.phase as i32, self.level, self.mode as i32, self.turn as i32, self.time,
+                              index(self.winner), index(self.death.map(|d| d as usize)), self.scores.len() as i32];
+
+        for p in 0..self.scores.len() { values.extend([self.scores[p], self.lives[p], self.timers[p]].iter()); }
+        values.extend(self.homes.iter().map(|h| index(*h)));
+        values
+    }
+
+    fn actor_values(&self) -> Vec<[i32; ACTOR_VALUES]>
+    {
+        self.actors.iter().map(|b| {
+            let sprite = b.sprite.unwrap_or(pt(-1, -1));
+            [b.kind as i32, b.pos.x, b.pos.y, b.size.x, b.size.y, sprite.x, sprite.y]
+        }).collect()
+    }
+
+    fn from_values(tick: i32, hud: &[i32], actors: &[[i32; ACTOR_VALUES]]) -> Result<View, String>
+    {
+        let get = |i: usize| hud.get(i).copied().ok_or(format!("Missing HUD value {}", i));
+        let pick = |i: usize, n: usize| -> Result<Option<usize>, String>
+        {
+            let v = get(i)?;
+            if v < 0 { Ok(None) } else if (v as usize) < n { Ok(Some(v as usize)) } else { Err(format!("Bad HUD value {}: {}", i, v)) }
+        };
+
+        let players = get(9)?;
+        if players < 1 { return Err(format!("Bad number of players: {}", players)); }
+
+        let players = players as usize;
+        let homes = (hud.len() as i32 - 10 - 3 * players as i32).max(0) as usize;
+        let mut view = View { tick,
+                              size: pt(get(0)?, get(1)?),
+                              phase: Phase::ALL[pick(2, Phase::ALL.len())?.ok_or("Missing phase")?],
+                              level: get(3)?,
+                              mode: Mode::ALL[pick(4, Mode::ALL.len())?.ok_or("Missing mode")?],
+                              turn: pick(5, players)?.ok_or("Missing turn")?,
+                              time: get(6)?,
+                              winner: pick(7, players)?,
+                              death: pick(8, DeathCause::ALL.len())?.map(|d| DeathCause::ALL[d]),
+                              scores: vec![],
+                              lives: vec![],
+                              timers: vec![],
+                              homes: vec![],
+                              actors: vec![] };
+
+        for p in 0..players
+        {
+            view.scores.push(get(10 + 3 * p)?);
+            view.lives.push(get(11 + 3 * p)?);
+            view.timers.push(get(12 + 3 * p)?);
+        }
+
+        for i in 0..homes { view.homes.push(pick(10 + 3 * players + i, players)?); }
+
+        for values in actors
+        {
+            let kind = *ActorKind::ALL.get(values[0] as usize).ok_or(format!("Bad actor kind: {}", values[0]))?;
+            let sprite = if values[5] < 0 { None } else { Some(pt(values[5], values[6])) };
+
+            view.actors.push(ActorView { kind, pos: pt(values[1], values[2]), size: pt(values[3], values[4]), sprite });
+        }
+
+        Ok(view)
+    }
+}
+
+fn join(values: &[i32]) -> String
+{
+    values.iter().map(|v| v.to_string()).collect::<Vec<String>>().join(",")
+}
+
+fn split(text: &str) -> Result<Vec<i32>, String>
+{
+    text.split(',').map(|v| v.parse::<i32>().map_err(|e| format!("Bad value {}: {}", v, e))).collect()
+}
+
+/// Turn the views of consecutive ticks into lines of text, each holding only the values changed since
+/// the previous tick. Full frames are sent at the beginning, every `KEYFRAME_TICKS` ticks, when actors
+/// are added or removed, and when a tick does not follow the previous one (a new game):
+/// - `K tick hud actors`: comma separated HUD values, and actor values separated by `;` (or `-`);
+/// - `D tick changes`: space separated changes, like `h6=12` for a HUD value or `a3.1=340` for the
+///   second value of the fourth actor.
+#[derive(Default)]
+pub struct StreamEncoder
+{
+    hud: Vec<i32>,
+    actors: Vec<[i32; ACTOR_VALUES]>,
+    keyframe: Option<i32>,
+    tick: i32
+}
+impl StreamEncoder
+{
+    pub fn new() -> StreamEncoder { StreamEncoder::default() }
+
+    pub fn encode(&mut self, view: &View) -> String
+    {
+        let hud = view.hud_values();
+        let actors = view.actor_values();
+        let full = match self.keyframe
+        {
+            Some(tick) => view.tick != self.tick + 1 || view.tick - tick >= KEYFRAME_TICKS || hud.len() != self.hud.len() || actors.len() != self.actors.len(),
+            None => true
+        };
+
+        let line = if full
+        {
+            self.keyframe = Some(view.tick);
+            let actor_list: Vec<String> = actors.iter().map(|a| join(a)).collect();
+            let actor_list = if actor_list.is_empty() { "-".to_string() } else { actor_list.join(";") };
+
+            format!("K {} {} {}", view.tick, join(&hud), actor_list)
+        }
+        else
+        {
+            let mut line = format!("D {}", view.tick);
+
+            for (i, (new, _)) in hud.iter().zip(&self.hud).enumerate().filter(|(_, (new, old))| new != old)
+            {
+                line += &format!(" h{}={}", i, new);
+            }
+
+            for (i, (new, old)) in actors.iter().zip(&self.actors).enumerate()
+            {
+                for f in (0..ACTOR_VALUES).filter(|f| new[*f] != old[*f])
+                {
+                    line += &format!(" a{}.{}={}", i, f, new[f]);
+                }
+            }
+
+            line
+        };
+
+        self.hud = hud;
+        self.actors = actors;
+        self.tick = view.tick;
+        line
+    }
+}
+
+/// Stream of a game played in the browser, gathered in batches of `BATCH_TICKS` lines to publish.
+#[derive(Default)]
+pub struct Broadcast
+{
+    encoder: StreamEncoder,
+    lines: Vec<String>,
+    tick: Option<i32>
+}
+impl Broadcast
+{
+    pub fn new() -> Broadcast { Broadcast::default() }
+
+    /// Add the view of a tick, returning the lines of the batch once full. Views of the tick already
+    /// added, as while the game is paused, are skipped.
+    pub fn push(&mut self, view: &View) -> Option<String>
+    {
+        if self.tick == Some(view.tick) { return None; }
+
+        self.tick = Some(view.tick);
+        self.lines.push(self.encoder.encode(view));
+
+        if self.lines.len() < BATCH_TICKS { return None; }
+        Some(self.lines.drain(..).map(|line| line + "\n").collect())
+    }
+}
+
+/// Rebuild the views from the lines of a `StreamEncoder`. Lines received before the first full frame
+/// are skipped; if a tick is missed, lines are skipped until the next full frame.
+#[derive(Default)]
+pub struct StreamDecoder
+{
+    hud: Vec<i32>,
+    actors: Vec<[i32; ACTOR_VALUES]>,
+    tick: Option<i32>
+}
+impl StreamDecoder
+{
+    pub fn new() -> StreamDecoder { StreamDecoder::default() }
+
+    /// View of the tick encoded by the line, if it can be rebuilt.
+    pub fn decode(&mut self, line: &str) -> Result<Option<View>, String>
+    {
+        let mut fields = line.split_whitespace();
+        let kind = fields.next().ok_or("Empty line")?;
+        let tick = fields.next().ok_or("Missing tick")?.parse::<i32>().map_err(|e| format!("Bad tick: {}", e))?;
+
+        match kind
+        {
+            "K" =>
+            {
+                let hud = split(fields.next().ok_or("Missing HUD values")?)?;
+                let mut actors: Vec<[i32; ACTOR_VALUES]> = vec![];
+
+                for actor in fields.next().ok_or("Missing actors")?.split(';').filter(|a| *a != "-")
+                {
+                    let values = split(actor)?;
+                    if values.len() != ACTOR_VALUES { return Err(format!("Bad actor: {}", actor)); }
+
+                    let mut array = [0; ACTOR_VALUES];
+                    array.copy_from_slice(&values);
+                    actors.push(array);
+                }
+
+                self.hud = hud;
+                self.actors = actors;
+            }
+            "D" if self.tick.is_some() && self.tick == tick.checked_sub(1) =>
+            {
+                for change in fields
+                {
+                    let (target, value) = change.split_once('=').ok_or(format!("Bad change: {}", change))?;
+                    let value = value.parse::<i32>().map_err(|e| format!("Bad change {}: {}", change, e))?;
+                    let index = |i: &str| i.parse::<usize>().map_err(|e| format!("Bad change {}: {}", change, e));
+
+                    if let Some(h) = target.strip_prefix('h')
+                    {
+                        *self.hud.get_mut(index(h)?).ok_or(format!("Bad change: {}", change))? = value;
+                    }
+                    else if let Some((a, f)) = target.strip_prefix('a').and_then(|a| a.split_once('.'))
+                    {
+                        let actor = self.actors.get_mut(index(a)?).ok_or(format!("Bad change: {}", change))?;
+                        *actor.get_mut(index(f)?).ok_or(format!("Bad change: {}", change))? = value;
+                    }
+                    else { return Err(format!("Bad change: {}", change)); }
+                }
+            }
+            "D" =>
+            {
+                // Not following the last tick decoded: wait for the next full frame
+                self.tick = None;
+                return Ok(None);
+            }
+            _ => return Err(format!("Unknown line: {}", kind))
+        }
+
+        self.tick = Some(tick);
+        View::from_values(tick, &self.hud, &self.actors).map(Some)
+    }
+}
@@ -0,0 +1,127 @@
+use std::io::Write;
+use std::net::TcpListener;
+use std::thread;
+use std::time::Duration;
+
+use hello_wasm::frogger::*;
+use hello_wasm::input::*;
+use hello_wasm::net::*;
+use hello_wasm::rand::Rng;
+
+const TICKS: u32 = 600;
+
+fn config() -> GameConfig
+{
+    GameConfig { seed: 42, players: 2, mode: Mode::Coop, ..GameConfig::default() }
+}
+
+/// A random direction every few ticks, different for each player.
+fn random_actions(rng: &mut Rng, tick: u32) -> Vec<Action>
+{
+    if tick.is_multiple_of(8) { vec![Action::ALL[rng.randint(0, 3) as usize]] } else { vec![] }
+}
+
+/// An address on localhost nobody listens to.
+fn free_addr() -> String
+{
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().to_string()
+}
+
+/// Play `TICKS` ticks as the given player, returning the state hash after each tick.
+fn play<T: Transport>(player: usize, transport: T) -> Result<Vec<u64>, String>
+{
+    let mut peer = Lockstep::new(config(), player, transport);
+    let mut rng = Rng::new(player as u32 + 1);
+    let mut hashes: Vec<u64> = vec![];
+
+    while peer.tick() < TICKS
+    {
+        let mut advanced = peer.update(&random_actions(&mut rng, peer.tick()))?;
+
+        while !advanced
+        {
+            thread::sleep(Duration::from_millis(1));
+            advanced = peer.update(&[])?;
+        }
+        hashes.push(state_hash(peer.game()));
+    }
+
+    assert_eq!(peer.desync(), None);
+    Ok(hashes)
+}
+
+#[test]
+fn loopback_peers_stay_in_sync()
+{
+    let (t1, t2) = LoopbackTransport::pair();
+    let mut peers = [Lockstep::new(config(), 0, t1), Lockstep::new(config(), 1, t2)];
+    let mut rngs = [Rng::new(1), Rng::new(2)];
+
+    while peers[0].tick() < TICKS
+    {
+        let tick = peers[0].tick();
+
+        for (peer, rng) in peers.iter_mut().zip(rngs.iter_mut())
+        {
+            peer.update(&random_actions(rng, tick)).unwrap();
+        }
+
+        // The first peer received the frame of the second one only after sending its own
+        peers[0].update(&[]).unwrap();
+
+        assert_eq!(peers[0].tick(), peers[1].tick());
+        assert_eq!(state_hash(peers[0].game()), state_hash(peers[1].game()), "tick {}", tick);
+    }
+
+    assert_eq!(peers[0].desync(), None);
+    assert_eq!(peers[1].desync(), None);
+    assert!(peers[0].game().frogs().iter().any(|hero| hero.score() > 0));
+}
+
+#[test]
+fn tcp_peers_stay_in_sync()
+{
+    let addr = free_addr();
+    let host_addr = addr.clone();
+    let host = thread::spawn(move || TcpTransport::listen(host_addr.as_str()).and_then(|t| play(0, t)));
+
+    // Retry until the host listens
+    let mut transport = TcpTransport::connect(addr.as_str());
+    for _ in 0..100
+    {
+        if transport.is_ok() { break; }
+        thread::sleep(Duration::from_millis(10));
+        transport = TcpTransport::connect(addr.as_str());
+    }
+
+    let joined = play(1, transport.unwrap()).unwrap();
+    let hosted = host.join().unwrap().unwrap();
+
+    assert_eq!(hosted.len(), TICKS as usize);
+    assert_eq!(hosted, joined);
+}
+
+#[test]
+fn frames_before_a_malformed_line_are_delivered()
+{
+    let addr = free_addr();
+    let listener = TcpListener::bind(addr.as_str()).unwrap();
+    let mut transport = TcpTransport::connect(addr.as_str()).unwrap();
+    let (mut peer, _) = listener.accept().unwrap();
+    let frame = InputFrame { tick: 0, player: 1, hash: 7, actions: vec![Action::Up] };
+
+    peer.write_all(format!("{}\nnot a frame\n", frame.to_line()).as_bytes()).unwrap();
+    peer.flush().unwrap();
+
+    let mut frames: Vec<InputFrame> = vec![];
+    for _ in 0..100
+    {
+        frames = transport.receive().unwrap();
+        if !frames.is_empty() { break; }
+        thread::sleep(Duration::from_millis(10));
+    }
+
+    assert_eq!(frames, vec![frame]);
+    assert!(transport.receive().is_err());
+}
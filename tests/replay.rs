@@ -0,0 +1,76 @@
+use std::collections::HashSet;
+
+use hello_wasm::frogger::*;
+use hello_wasm::input::*;
+use hello_wasm::rand::Rng;
+use hello_wasm::replay::*;
+
+/// Play a co-op game to its end with random keys, the first player with the vim keys, recording it
+/// the way the GUI does.
+fn record(seed: u32) -> (Replay, FroggerGame)
+{
+    let config = GameConfig { seed, players: 2, mode: Mode::Coop, ..GameConfig::default() };
+    let mut game = FroggerGame::with_config(config);
+    let mut input = InputState::new();
+    let mut replay = Replay::new(config);
+    let mut rng = Rng::new(seed);
+    let keys = [Key::Char('k'), Key::Char('j'), Key::Char('h'), Key::Char('l'), Key::Char('w'), Key::Char('a'), Key::Char('d'), Key::Char('x')];
+
+    input.set_bindings(KeyBindings::preset("vim").unwrap());
+
+    while !game.game_over() && game.ticks() < 20_000
+    {
+        let presses = if game.ticks() % 5 == 0 { vec![keys[rng.randint(0, keys.len() as i32 - 1) as usize]] } else { vec![] };
+
+        input.update_with_presses(HashSet::new(), presses);
+        replay.record(&game, &input);
+        game.tick(&input);
+    }
+
+    (replay, game)
+}
+
+#[test]
+fn replay_round_trip()
+{
+    let (replay, game) = record(11);
+    assert!(game.game_over());
+    assert!(replay.frames.iter().any(|f| f.player == 0) && replay.frames.iter().any(|f| f.player == 1));
+
+    let text = replay.to_text();
+    let parsed = Replay::from_text(&text).unwrap();
+
+    assert_eq!(parsed.ticks, replay.ticks);
+    assert_eq!(parsed.frames, replay.frames);
+    assert_eq!(parsed.to_text(), text);
+    assert_eq!(parsed.digest(), replay.digest());
+    assert!(parsed.check_config().is_ok());
+
+    let outcome = verify(&parsed);
+    let players = 0..game.players().len();
+
+    assert_eq!(outcome.scores, players.clone().map(|p| game.player_score(p)).collect::<Vec<i32>>());
+    assert_eq!(outcome.lives, players.clone().map(|p| game.player_lives(p)).collect::<Vec<i32>>());
+    assert_eq!(outcome.levels, players.map(|p| game.players()[p].level).collect::<Vec<i32>>());
+    assert_eq!(outcome.homes, game.homes());
+    assert_eq!(outcome.ticks, game.ticks());
+    assert!(outcome.finished);
+    assert!(outcome.scores.iter().any(|s| *s > 0));
+}
+
+#[test]
+fn replay_without_the_moves_of_a_player_does_not_verify()
+{
+    let (replay, game) = record(12);
+    let player = (0..2).find(|p| game.player_score(*p) > 0).unwrap();
+    let mut text = replay.to_text();
+
+    // Frames are `tick player actions` lines
+    text = text.lines().filter(|line| line.split(' ').nth(1) != Some(&player.to_string()) || line.split(' ').count() != 3)
+               .map(|line| line.to_string() + "\n").collect();
+
+    let tampered = Replay::from_text(&text).unwrap();
+    assert!(tampered.frames.iter().all(|f| f.player != player));
+    assert_ne!(tampered.digest(), replay.digest());
+    assert_eq!(verify(&tampered).scores[player], 0);
+}
@@ -0,0 +1,29 @@
+use hello_wasm::input::{Action, Key, KeyBindings};
+use hello_wasm::settings::Settings;
+
+#[test]
+fn key_bindings_to_any_key_round_trip()
+{
+    let config = "Up = ;, \\\nDown = s\n# comment ; \\n";
+    let mut settings = Settings::default();
+    settings.set_key_bindings(1, config);
+
+    let text = settings.to_text();
+    assert_eq!(text.lines().count(), 5);
+
+    let parsed = Settings::from_text(&text).unwrap();
+    assert_eq!(parsed, settings);
+
+    let bindings = KeyBindings::from_config(&parsed.key_bindings[0].1).unwrap();
+    assert_eq!(bindings.keys(Action::Up), &[Key::Char(';'), Key::Char('\\')]);
+}
+
+#[test]
+fn key_bindings_of_players_out_of_range_are_dropped()
+{
+    let text = "master 0.5\nkeys 1000000000 wasd\nkeys x vim\nkeys 0 Up w\nkeys 1 Jump = w\nkeys 1 vim\n";
+    let settings = Settings::from_text(text).unwrap();
+
+    assert_eq!(settings.audio.master, 0.5);
+    assert_eq!(settings.key_bindings, vec![(1, "vim".to_string())]);
+}
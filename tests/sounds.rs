@@ -0,0 +1,45 @@
+use hello_wasm::audio::{LogSink, Sound, SoundSink};
+use hello_wasm::frogger::*;
+use hello_wasm::input::InputState;
+use hello_wasm::replay::{tick_with_actions, InputSource, Replay, ReplayInput};
+
+/// A game of the bot on seed 1: it takes a home slot, then gets squashed.
+const HOME_AND_DEATH: &str = include_str!("data/home_and_death.txt");
+
+#[test]
+fn sounds_of_a_home_and_a_death()
+{
+    let replay = Replay::from_text(HOME_AND_DEATH).unwrap();
+    let mut game = FroggerGame::with_config(replay.config);
+    let mut input = InputState::new();
+    let mut source = ReplayInput::new(&replay);
+    let mut sink = LogSink::new(false);
+    let mut hops = 0;
+
+    for _ in 0..replay.ticks
+    {
+        let actions = source.actions(&game);
+        let frogs: Vec<bool> = game.frogs().iter().map(|hero| hero.hopping()).collect();
+
+        tick_with_actions(&mut game, &mut input, &actions);
+        hops += game.frogs().iter().zip(&frogs).filter(|(hero, was)| hero.hopping() && !**was).count();
+        sink.play_all(game.sounds());
+    }
+
+    let deaths: Vec<DeathCause> = game.stats().deaths().iter().map(|d| d.cause).collect();
+    assert_eq!(deaths, vec![DeathCause::Squashed]);
+    assert_eq!(game.homes().iter().filter(|h| h.is_some()).count(), 1);
+
+    assert_eq!(sink.count(Sound::Home), 1);
+    assert_eq!(sink.count(Sound::Squash), 1);
+    assert_eq!(sink.count(Sound::Splash), 0);
+    assert!(hops > 0);
+    assert_eq!(sink.count(Sound::Hop), hops);
+    assert_eq!(sink.count(Sound::ExtraLife), 0);
+    assert_eq!(sink.count(Sound::LevelClear), 0);
+
+    // The home slot comes before the death
+    let home = sink.played.iter().position(|s| *s == Sound::Home).unwrap();
+    let squash = sink.played.iter().position(|s| *s == Sound::Squash).unwrap();
+    assert!(home < squash);
+}
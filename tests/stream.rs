@@ -0,0 +1,108 @@
+use hello_wasm::frogger::*;
+use hello_wasm::input::InputState;
+use hello_wasm::replay::{tick_with_actions, InputSource, RandomInput};
+use hello_wasm::stream::*;
+
+/// Views of the first ticks of a game with random hops.
+fn views(seed: u32, ticks: i32) -> Vec<View>
+{
+    let mut game = FroggerGame::with_config(GameConfig { seed, ..GameConfig::default() });
+    let mut input = InputState::new();
+    let mut source = RandomInput::new(seed, 6);
+    let mut views: Vec<View> = vec![];
+
+    for _ in 0..ticks
+    {
+        let actions = source.actions(&game);
+        tick_with_actions(&mut game, &mut input, &actions);
+        views.push(View::of(&game));
+    }
+
+    views
+}
+
+#[test]
+fn stream_round_trip()
+{
+    let views = views(3, 400);
+    let mut encoder = StreamEncoder::new();
+    let mut decoder = StreamDecoder::new();
+    let (mut full, mut deltas) = (0, 0);
+
+    for view in &views
+    {
+        let line = encoder.encode(view);
+        let decoded = decoder.decode(&line).unwrap().unwrap();
+
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", view));
+        if line.starts_with("K ") { full += line.len(); } else { deltas += line.len(); }
+    }
+
+    // Full frames are few, and much longer than the changes between them
+    let keyframes = (views.len() as i32 / KEYFRAME_TICKS) as usize;
+    assert!(deltas / (views.len() - keyframes) < full / keyframes);
+}
+
+#[test]
+fn spectators_join_at_the_next_full_frame()
+{
+    let views = views(5, 200);
+    let mut encoder = StreamEncoder::new();
+    let lines: Vec<String> = views.iter().map(|view| encoder.encode(view)).collect();
+    let mut decoder = StreamDecoder::new();
+    let first = lines.iter().skip(10).position(|line| line.starts_with("K ")).unwrap() + 10;
+
+    for line in &lines[10..first] { assert!(decoder.decode(line).unwrap().is_none()); }
+
+    for (line, view) in lines.iter().zip(&views).skip(first)
+    {
+        assert_eq!(format!("{:?}", decoder.decode(line).unwrap().unwrap()), format!("{:?}", view));
+    }
+}
+
+#[test]
+fn a_new_game_starts_with_a_full_frame()
+{
+    let mut encoder = StreamEncoder::new();
+    let mut decoder = StreamDecoder::new();
+
+    for view in views(1, 100).iter().chain(views(2, 20).iter())
+    {
+        let decoded = decoder.decode(&encoder.encode(view)).unwrap().unwrap();
+        assert_eq!(format!("{:?}", decoded), format!("{:?}", view));
+    }
+}
+
+#[test]
+fn broadcast_batches_ticks_once_each()
+{
+    let views = views(4, 2 * BATCH_TICKS as i32);
+    let mut broadcast = Broadcast::new();
+    let mut batches: Vec<String> = vec![];
+
+    for view in &views
+    {
+        // The same tick again, as while the game is paused
+        batches.extend(broadcast.push(view));
+        batches.extend(broadcast.push(view));
+    }
+
+    assert_eq!(batches.len(), 2);
+    assert!(batches.iter().all(|batch| batch.lines().count() == BATCH_TICKS));
+
+    let mut decoder = StreamDecoder::new();
+    let decoded: Vec<String> = batches.iter().flat_map(|batch| batch.lines())
+                                      .map(|line| format!("{:?}", decoder.decode(line).unwrap().unwrap())).collect();
+    let expected: Vec<String> = views.iter().map(|view| format!("{:?}", view)).collect();
+    assert_eq!(decoded, expected);
+}
+
+#[test]
+fn bad_ticks_are_not_decoded()
+{
+    let mut decoder = StreamDecoder::new();
+
+    assert!(decoder.decode("D -2147483648 h0=1").unwrap().is_none());
+    assert!(decoder.decode("D 2147483647 h0=1").unwrap().is_none());
+    assert!(decoder.decode("K x 1 -").is_err());
+}